@@ -98,7 +98,7 @@ const Report = () => {
 
           <Typography variant="h6" sx={{ fontSize: '14px', mt: 4 }}>Latest Log Output</Typography>
 
-          {data.last_event.log && <LogMessages log={JSON.parse(data.last_event.log)} />}
+          {data.last_event.log && <LogMessages log={data.last_event.log} />}
 
           {!data.last_event.log && <NoLogMessages />}
         </>
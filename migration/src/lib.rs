@@ -14,6 +14,25 @@ mod m20250204_035650_organization_stats;
 mod m20250219_073234_report_stat_spiking;
 mod m20250219_093632_org_requests_alert;
 mod m20250908_130953_environment_notification_settings;
+mod m20261017_090000_queued_events;
+mod m20261017_100000_report_event_frames;
+mod m20261017_110000_project_grouping;
+mod m20261017_120000_project_grouping_rules;
+mod m20261017_130000_report_merges;
+mod m20261017_140000_project_retention;
+mod m20261017_150000_report_event_metadata;
+mod m20261017_160000_event_occurrence;
+mod m20261017_170000_event_tags;
+mod m20261017_180000_releases;
+mod m20261017_190000_report_ignore;
+mod m20261017_200000_project_inbound_filters;
+mod m20261017_210000_project_rate_limits;
+mod m20261017_220000_project_keys;
+mod m20261017_230000_environment_keys;
+mod m20261017_240000_environment_management;
+mod m20261017_250000_notification_channels;
+mod m20261017_260000_notification_deliveries;
+mod m20261017_270000_report_merge_state;
 
 pub struct Migrator;
 
@@ -34,6 +53,25 @@ impl MigratorTrait for Migrator {
             Box::new(m20250219_073234_report_stat_spiking::Migration),
             Box::new(m20250219_093632_org_requests_alert::Migration),
             Box::new(m20250908_130953_environment_notification_settings::Migration),
+            Box::new(m20261017_090000_queued_events::Migration),
+            Box::new(m20261017_100000_report_event_frames::Migration),
+            Box::new(m20261017_110000_project_grouping::Migration),
+            Box::new(m20261017_120000_project_grouping_rules::Migration),
+            Box::new(m20261017_130000_report_merges::Migration),
+            Box::new(m20261017_140000_project_retention::Migration),
+            Box::new(m20261017_150000_report_event_metadata::Migration),
+            Box::new(m20261017_160000_event_occurrence::Migration),
+            Box::new(m20261017_170000_event_tags::Migration),
+            Box::new(m20261017_180000_releases::Migration),
+            Box::new(m20261017_190000_report_ignore::Migration),
+            Box::new(m20261017_200000_project_inbound_filters::Migration),
+            Box::new(m20261017_210000_project_rate_limits::Migration),
+            Box::new(m20261017_220000_project_keys::Migration),
+            Box::new(m20261017_230000_environment_keys::Migration),
+            Box::new(m20261017_240000_environment_management::Migration),
+            Box::new(m20261017_250000_notification_channels::Migration),
+            Box::new(m20261017_260000_notification_deliveries::Migration),
+            Box::new(m20261017_270000_report_merge_state::Migration),
         ]
     }
 }
@@ -0,0 +1,98 @@
+use sea_orm_migration::prelude::*;
+
+use crate::TableDefaults;
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    ProjectId,
+}
+
+#[derive(DeriveIden)]
+enum QueuedEvents {
+    Table,
+    QueuedEventId,
+    ProjectId,
+    Payload,
+    Status,
+    Attempts,
+    AvailableAt,
+    LastError,
+    Created,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(QueuedEvents::Table)
+                    .if_not_exists()
+                    .apply_defaults(manager)
+                    .col(
+                        ColumnDef::new(QueuedEvents::QueuedEventId)
+                            .unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(ColumnDef::new(QueuedEvents::ProjectId).unsigned().not_null())
+                    // raw events can exceed the 64KB limit of mysql TEXT columns
+                    .col(
+                        ColumnDef::new(QueuedEvents::Payload)
+                            .custom(Alias::new("LONGTEXT"))
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(QueuedEvents::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(ColumnDef::new(QueuedEvents::Attempts).unsigned().not_null().default(0))
+                    .col(ColumnDef::new(QueuedEvents::AvailableAt).date_time().not_null())
+                    .col(ColumnDef::new(QueuedEvents::LastError).text().null())
+                    .col(
+                        ColumnDef::new(QueuedEvents::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_queued_events_1")
+                            .from_col(QueuedEvents::ProjectId)
+                            .to(Projects::Table, Projects::ProjectId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_queued_events_1")
+                    .table(QueuedEvents::Table)
+                    .col(QueuedEvents::Status)
+                    .col(QueuedEvents::AvailableAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().if_exists().table(QueuedEvents::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
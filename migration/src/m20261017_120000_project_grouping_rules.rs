@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+use crate::TableDefaults;
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    ProjectId,
+}
+
+#[derive(DeriveIden)]
+enum ProjectGroupingRules {
+    Table,
+    ProjectGroupingRuleId,
+    ProjectId,
+    Position,
+    Field,
+    Action,
+    Pattern,
+    Replacement,
+    Created,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectGroupingRules::Table)
+                    .if_not_exists()
+                    .apply_defaults(manager)
+                    .col(
+                        ColumnDef::new(ProjectGroupingRules::ProjectGroupingRuleId)
+                            .unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(ColumnDef::new(ProjectGroupingRules::ProjectId).unsigned().not_null())
+                    .col(
+                        ColumnDef::new(ProjectGroupingRules::Position)
+                            .unsigned()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(ProjectGroupingRules::Field).string_len(16).not_null())
+                    .col(ColumnDef::new(ProjectGroupingRules::Action).string_len(16).not_null())
+                    .col(ColumnDef::new(ProjectGroupingRules::Pattern).string_len(255).not_null())
+                    .col(ColumnDef::new(ProjectGroupingRules::Replacement).string_len(255).null())
+                    .col(
+                        ColumnDef::new(ProjectGroupingRules::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_grouping_rules_1")
+                            .from_col(ProjectGroupingRules::ProjectId)
+                            .to(Projects::Table, Projects::ProjectId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().if_exists().table(ProjectGroupingRules::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::TableDefaults;
+
+#[derive(DeriveIden)]
+enum ProjectReports {
+    Table,
+    ProjectReportId,
+}
+
+#[derive(DeriveIden)]
+enum ProjectEnvironments {
+    Table,
+    ProjectEnvironmentId,
+}
+
+#[derive(DeriveIden)]
+enum ProjectReportEvents {
+    Table,
+    ProjectReportMergeId,
+}
+
+#[derive(DeriveIden)]
+enum ProjectReportMerges {
+    Table,
+    ProjectReportMergeId,
+    ProjectReportId,
+    ProjectEnvironmentId,
+    Uid,
+    Title,
+    LastSeen,
+    IsSeen,
+    IsResolved,
+    ReportCreated,
+    Stats,
+    Created,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectReportMerges::Table)
+                    .if_not_exists()
+                    .apply_defaults(manager)
+                    .col(
+                        ColumnDef::new(ProjectReportMerges::ProjectReportMergeId)
+                            .unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectReportMerges::ProjectReportId)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectReportMerges::ProjectEnvironmentId)
+                            .unsigned()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectReportMerges::Uid)
+                            .string_len(64)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(ProjectReportMerges::Title).string_len(500).not_null())
+                    .col(ColumnDef::new(ProjectReportMerges::LastSeen).date_time().not_null())
+                    .col(ColumnDef::new(ProjectReportMerges::IsSeen).tiny_integer().not_null())
+                    .col(
+                        ColumnDef::new(ProjectReportMerges::IsResolved)
+                            .tiny_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ProjectReportMerges::ReportCreated).date_time().null())
+                    // counters of the merged report, subtracted again when it is split back out
+                    .col(
+                        ColumnDef::new(ProjectReportMerges::Stats)
+                            .custom(Alias::new("LONGTEXT"))
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectReportMerges::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_report_merges_1")
+                            .from_col(ProjectReportMerges::ProjectReportId)
+                            .to(ProjectReports::Table, ProjectReports::ProjectReportId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_report_merges_2")
+                            .from_col(ProjectReportMerges::ProjectEnvironmentId)
+                            .to(ProjectEnvironments::Table, ProjectEnvironments::ProjectEnvironmentId)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // events that belong to a merged report, so they can be moved back on unmerge
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectReportEvents::Table)
+                    .add_column(unsigned_null(ProjectReportEvents::ProjectReportMergeId))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_report_events_merge")
+                    .table(ProjectReportEvents::Table)
+                    .col(ProjectReportEvents::ProjectReportMergeId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_project_report_events_merge")
+                    .table(ProjectReportEvents::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectReportEvents::Table)
+                    .drop_column(ProjectReportEvents::ProjectReportMergeId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().if_exists().table(ProjectReportMerges::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveIden)]
+enum ProjectReportEvents {
+    Table,
+    Os,
+    Arch,
+    Version,
+    ThreadId,
+    ThreadName,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sqlite can only add one column per statement
+        let columns = [
+            string_len_null(ProjectReportEvents::Os, 64),
+            string_len_null(ProjectReportEvents::Arch, 64),
+            string_len_null(ProjectReportEvents::Version, 64),
+            string_len_null(ProjectReportEvents::ThreadId, 64),
+            string_len_null(ProjectReportEvents::ThreadName, 255),
+        ];
+
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectReportEvents::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            ProjectReportEvents::Os,
+            ProjectReportEvents::Arch,
+            ProjectReportEvents::Version,
+            ProjectReportEvents::ThreadId,
+            ProjectReportEvents::ThreadName,
+        ];
+
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectReportEvents::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveIden)]
+enum QueuedEvents {
+    Table,
+    ClientIpHash,
+}
+
+#[derive(DeriveIden)]
+enum ProjectReportEvents {
+    Table,
+    LocationFile,
+    LocationLine,
+    LocationColumn,
+    Received,
+    ClientIpHash,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(QueuedEvents::Table)
+                    .add_column(string_len_null(QueuedEvents::ClientIpHash, 64))
+                    .to_owned(),
+            )
+            .await?;
+
+        // sqlite can only add one column per statement
+        let columns = [
+            string_len_null(ProjectReportEvents::LocationFile, 255),
+            unsigned_null(ProjectReportEvents::LocationLine),
+            unsigned_null(ProjectReportEvents::LocationColumn),
+            date_time_null(ProjectReportEvents::Received),
+            string_len_null(ProjectReportEvents::ClientIpHash, 64),
+        ];
+
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectReportEvents::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            ProjectReportEvents::LocationFile,
+            ProjectReportEvents::LocationLine,
+            ProjectReportEvents::LocationColumn,
+            ProjectReportEvents::Received,
+            ProjectReportEvents::ClientIpHash,
+        ];
+
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectReportEvents::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(QueuedEvents::Table)
+                    .drop_column(QueuedEvents::ClientIpHash)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
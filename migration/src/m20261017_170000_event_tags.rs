@@ -0,0 +1,58 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveIden)]
+enum ProjectReportEvents {
+    Table,
+    Tags,
+    Context,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectReportEvents::Table)
+                    .add_column(text_null(ProjectReportEvents::Tags))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectReportEvents::Table)
+                    .add_column(text_null(ProjectReportEvents::Context))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectReportEvents::Table)
+                    .drop_column(ProjectReportEvents::Context)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectReportEvents::Table)
+                    .drop_column(ProjectReportEvents::Tags)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::TableDefaults;
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    ProjectId,
+}
+
+#[derive(DeriveIden)]
+enum ProjectReleases {
+    Table,
+    ProjectReleaseId,
+    ProjectId,
+    Version,
+    FirstSeen,
+    LastSeen,
+}
+
+#[derive(DeriveIden)]
+enum ProjectReports {
+    Table,
+    FirstVersion,
+    LastVersion,
+    ResolvedInVersion,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectReleases::Table)
+                    .if_not_exists()
+                    .apply_defaults(manager)
+                    .col(
+                        ColumnDef::new(ProjectReleases::ProjectReleaseId)
+                            .unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(ColumnDef::new(ProjectReleases::ProjectId).unsigned().not_null())
+                    .col(ColumnDef::new(ProjectReleases::Version).string_len(64).not_null())
+                    .col(
+                        ColumnDef::new(ProjectReleases::FirstSeen)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectReleases::LastSeen)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_releases_1")
+                            .from_col(ProjectReleases::ProjectId)
+                            .to(Projects::Table, Projects::ProjectId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_releases_1")
+                    .table(ProjectReleases::Table)
+                    .col(ProjectReleases::ProjectId)
+                    .col(ProjectReleases::Version)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // sqlite can only add one column per statement
+        let columns = [
+            string_len_null(ProjectReports::FirstVersion, 64),
+            string_len_null(ProjectReports::LastVersion, 64),
+            string_len_null(ProjectReports::ResolvedInVersion, 64),
+        ];
+
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectReports::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            ProjectReports::FirstVersion,
+            ProjectReports::LastVersion,
+            ProjectReports::ResolvedInVersion,
+        ];
+
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectReports::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().if_exists().table(ProjectReleases::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
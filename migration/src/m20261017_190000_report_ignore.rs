@@ -0,0 +1,64 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveIden)]
+enum ProjectReports {
+    Table,
+    IsIgnored,
+    IgnoreCondition,
+    IgnoredUntil,
+    IgnoredEventsLeft,
+    IgnoredInVersion,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sqlite can only add one column per statement
+        let columns = [
+            tiny_integer(ProjectReports::IsIgnored).default(0).to_owned(),
+            string_len_null(ProjectReports::IgnoreCondition, 16),
+            date_time_null(ProjectReports::IgnoredUntil),
+            unsigned_null(ProjectReports::IgnoredEventsLeft),
+            string_len_null(ProjectReports::IgnoredInVersion, 64),
+        ];
+
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectReports::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            ProjectReports::IsIgnored,
+            ProjectReports::IgnoreCondition,
+            ProjectReports::IgnoredUntil,
+            ProjectReports::IgnoredEventsLeft,
+            ProjectReports::IgnoredInVersion,
+        ];
+
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectReports::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
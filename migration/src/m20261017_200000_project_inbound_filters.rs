@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+use crate::TableDefaults;
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    ProjectId,
+}
+
+#[derive(DeriveIden)]
+enum ProjectInboundFilters {
+    Table,
+    ProjectInboundFilterId,
+    ProjectId,
+    Field,
+    Pattern,
+    Created,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectInboundFilters::Table)
+                    .if_not_exists()
+                    .apply_defaults(manager)
+                    .col(
+                        ColumnDef::new(ProjectInboundFilters::ProjectInboundFilterId)
+                            .unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(ColumnDef::new(ProjectInboundFilters::ProjectId).unsigned().not_null())
+                    .col(ColumnDef::new(ProjectInboundFilters::Field).string_len(16).not_null())
+                    .col(
+                        ColumnDef::new(ProjectInboundFilters::Pattern)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInboundFilters::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_inbound_filters_1")
+                            .from_col(ProjectInboundFilters::ProjectId)
+                            .to(Projects::Table, Projects::ProjectId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().if_exists().table(ProjectInboundFilters::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    RateLimit,
+    ReportRateLimit,
+    SpikeProtection,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // limits are events per minute, null means unlimited
+        let columns = [
+            unsigned_null(Projects::RateLimit),
+            unsigned_null(Projects::ReportRateLimit),
+            tiny_integer(Projects::SpikeProtection).default(0).to_owned(),
+        ];
+
+        for mut column in columns {
+            manager
+                .alter_table(Table::alter().table(Projects::Table).add_column(&mut column).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            Projects::SpikeProtection,
+            Projects::ReportRateLimit,
+            Projects::RateLimit,
+        ] {
+            manager
+                .alter_table(Table::alter().table(Projects::Table).drop_column(column).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+}
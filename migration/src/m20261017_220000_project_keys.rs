@@ -0,0 +1,161 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::TableDefaults;
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    ProjectId,
+    ApiKey,
+    Created,
+}
+
+#[derive(DeriveIden)]
+enum ProjectKeys {
+    Table,
+    ProjectKeyId,
+    ProjectId,
+    Key,
+    Label,
+    Created,
+    LastUsed,
+    Expires,
+    Revoked,
+}
+
+#[derive(DeriveIden)]
+enum QueuedEvents {
+    Table,
+    ProjectKeyId,
+}
+
+#[derive(DeriveIden)]
+enum ProjectReportEvents {
+    Table,
+    ProjectKeyId,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectKeys::Table)
+                    .if_not_exists()
+                    .apply_defaults(manager)
+                    .col(
+                        ColumnDef::new(ProjectKeys::ProjectKeyId)
+                            .unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(ColumnDef::new(ProjectKeys::ProjectId).unsigned().not_null())
+                    .col(ColumnDef::new(ProjectKeys::Key).char_len(32).not_null())
+                    .col(ColumnDef::new(ProjectKeys::Label).string_len(80).not_null())
+                    .col(
+                        ColumnDef::new(ProjectKeys::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(ProjectKeys::LastUsed).date_time().null())
+                    .col(ColumnDef::new(ProjectKeys::Expires).date_time().null())
+                    .col(ColumnDef::new(ProjectKeys::Revoked).date_time().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_keys_1")
+                            .from_col(ProjectKeys::ProjectId)
+                            .to(Projects::Table, Projects::ProjectId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_keys_1")
+                    .table(ProjectKeys::Table)
+                    .col(ProjectKeys::Key)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // existing project keys keep working
+        let copy_keys = Query::insert()
+            .into_table(ProjectKeys::Table)
+            .columns([
+                ProjectKeys::ProjectId,
+                ProjectKeys::Key,
+                ProjectKeys::Label,
+                ProjectKeys::Created,
+            ])
+            .select_from(
+                Query::select()
+                    .column(Projects::ProjectId)
+                    .column(Projects::ApiKey)
+                    .expr(Expr::val("Default"))
+                    .column(Projects::Created)
+                    .from(Projects::Table)
+                    .to_owned(),
+            )
+            .map_err(|e| DbErr::Migration(e.to_string()))?
+            .to_owned();
+
+        manager.exec_stmt(copy_keys).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(QueuedEvents::Table)
+                    .add_column(unsigned_null(QueuedEvents::ProjectKeyId))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectReportEvents::Table)
+                    .add_column(unsigned_null(ProjectReportEvents::ProjectKeyId))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectReportEvents::Table)
+                    .drop_column(ProjectReportEvents::ProjectKeyId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(QueuedEvents::Table)
+                    .drop_column(QueuedEvents::ProjectKeyId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().if_exists().table(ProjectKeys::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
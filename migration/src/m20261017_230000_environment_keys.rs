@@ -0,0 +1,65 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveIden)]
+enum ProjectKeys {
+    Table,
+    ProjectEnvironmentId,
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    AutoCreateEnvironments,
+    DefaultEnvironmentId,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // keys bound to an environment can only send events to it
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectKeys::Table)
+                    .add_column(unsigned_null(ProjectKeys::ProjectEnvironmentId))
+                    .to_owned(),
+            )
+            .await?;
+
+        // unknown environments are mapped to the default one, or rejected when there's none
+        let columns = [
+            tiny_integer(Projects::AutoCreateEnvironments).default(1).to_owned(),
+            unsigned_null(Projects::DefaultEnvironmentId),
+        ];
+
+        for mut column in columns {
+            manager
+                .alter_table(Table::alter().table(Projects::Table).add_column(&mut column).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [Projects::DefaultEnvironmentId, Projects::AutoCreateEnvironments] {
+            manager
+                .alter_table(Table::alter().table(Projects::Table).drop_column(column).to_owned())
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectKeys::Table)
+                    .drop_column(ProjectKeys::ProjectEnvironmentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveIden)]
+enum ProjectEnvironments {
+    Table,
+    IsHidden,
+    GroupingName,
+    MergedIntoId,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // grouping_name keeps the name report uids were computed with after a rename,
+        // merged environments stay around so their events are assigned to the merge target
+        let columns = [
+            tiny_integer(ProjectEnvironments::IsHidden).default(0).to_owned(),
+            string_len_null(ProjectEnvironments::GroupingName, 80),
+            unsigned_null(ProjectEnvironments::MergedIntoId),
+        ];
+
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectEnvironments::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            ProjectEnvironments::MergedIntoId,
+            ProjectEnvironments::GroupingName,
+            ProjectEnvironments::IsHidden,
+        ];
+
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectEnvironments::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
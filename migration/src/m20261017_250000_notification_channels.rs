@@ -0,0 +1,412 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::TableDefaults;
+
+#[derive(DeriveIden, Clone, Copy)]
+enum Projects {
+    Table,
+    ProjectId,
+    SlackBotToken,
+    SlackChannel,
+    SlackWebhook,
+    Webhook,
+    TeamsWebhook,
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum ProjectEnvironments {
+    Table,
+    ProjectEnvironmentId,
+    ProjectId,
+    SlackChannel,
+    SlackWebhook,
+    Webhook,
+    TeamsWebhook,
+}
+
+#[derive(DeriveIden)]
+enum ProjectNotificationChannels {
+    Table,
+    ProjectNotificationChannelId,
+    ProjectId,
+    ProjectEnvironmentId,
+    Channel,
+    Config,
+    IsEnabled,
+    Created,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// webhook integrations with their project and environment columns, stored under the "url" config key
+const WEBHOOK_COLUMNS: [(&str, Projects, ProjectEnvironments); 3] = [
+    (
+        "slack_webhook",
+        Projects::SlackWebhook,
+        ProjectEnvironments::SlackWebhook,
+    ),
+    (
+        "teams_webhook",
+        Projects::TeamsWebhook,
+        ProjectEnvironments::TeamsWebhook,
+    ),
+    ("webhook", Projects::Webhook, ProjectEnvironments::Webhook),
+];
+
+fn json_object(pairs: Vec<(&str, SimpleExpr)>) -> SimpleExpr {
+    let args = pairs
+        .into_iter()
+        .flat_map(|(key, value)| [Expr::val(key).into(), value]);
+
+    Func::cust(Alias::new("json_object")).args(args).into()
+}
+
+fn copy_channels(
+    channel: &str,
+    project_config: SimpleExpr,
+    project_column: Projects,
+) -> Result<InsertStatement, DbErr> {
+    Ok(Query::insert()
+        .into_table(ProjectNotificationChannels::Table)
+        .columns([
+            ProjectNotificationChannels::ProjectId,
+            ProjectNotificationChannels::Channel,
+            ProjectNotificationChannels::Config,
+        ])
+        .select_from(
+            Query::select()
+                .column(Projects::ProjectId)
+                .expr(Expr::val(channel))
+                .expr(project_config)
+                .from(Projects::Table)
+                .and_where(Expr::col(project_column).is_not_null())
+                .to_owned(),
+        )
+        .map_err(|e| DbErr::Migration(e.to_string()))?
+        .to_owned())
+}
+
+// environment settings of "-1" turned notifications off for the environment
+fn copy_environment_overrides(channel: &str, key: &str, column: ProjectEnvironments) -> Result<InsertStatement, DbErr> {
+    let config = Expr::case(Expr::col(column).eq("-1"), Expr::val("{}"))
+        .finally(json_object(vec![(key, Expr::col(column).into())]));
+
+    let is_enabled = Expr::case(Expr::col(column).eq("-1"), 0).finally(1);
+
+    Ok(Query::insert()
+        .into_table(ProjectNotificationChannels::Table)
+        .columns([
+            ProjectNotificationChannels::ProjectId,
+            ProjectNotificationChannels::ProjectEnvironmentId,
+            ProjectNotificationChannels::Channel,
+            ProjectNotificationChannels::Config,
+            ProjectNotificationChannels::IsEnabled,
+        ])
+        .select_from(
+            Query::select()
+                .column(ProjectEnvironments::ProjectId)
+                .column(ProjectEnvironments::ProjectEnvironmentId)
+                .expr(Expr::val(channel))
+                .expr(config)
+                .expr(is_enabled)
+                .from(ProjectEnvironments::Table)
+                .and_where(Expr::col(column).is_not_null())
+                .to_owned(),
+        )
+        .map_err(|e| DbErr::Migration(e.to_string()))?
+        .to_owned())
+}
+
+fn config_value(backend: DbBackend, key: &str) -> SimpleExpr {
+    let value = Func::cust(Alias::new("json_extract"))
+        .arg(Expr::col((
+            ProjectNotificationChannels::Table,
+            ProjectNotificationChannels::Config,
+        )))
+        .arg(format!("$.{key}"));
+
+    match backend {
+        DbBackend::MySql => Func::cust(Alias::new("json_unquote")).arg(value).into(),
+        _ => value.into(),
+    }
+}
+
+fn channel_setting(channel: &str, value: SimpleExpr, condition: SimpleExpr) -> SimpleExpr {
+    SimpleExpr::SubQuery(
+        None,
+        Box::new(
+            Query::select()
+                .expr(value)
+                .from(ProjectNotificationChannels::Table)
+                .and_where(
+                    Expr::col((ProjectNotificationChannels::Table, ProjectNotificationChannels::Channel)).eq(channel),
+                )
+                .and_where(condition)
+                .limit(1)
+                .to_owned()
+                .into_sub_query_statement(),
+        ),
+    )
+}
+
+// copies a channel setting back into its old project column
+fn restore_channel(backend: DbBackend, channel: &str, key: &str, column: Projects) -> UpdateStatement {
+    let condition = Expr::col((
+        ProjectNotificationChannels::Table,
+        ProjectNotificationChannels::ProjectId,
+    ))
+    .equals((Projects::Table, Projects::ProjectId))
+    .and(
+        Expr::col((
+            ProjectNotificationChannels::Table,
+            ProjectNotificationChannels::ProjectEnvironmentId,
+        ))
+        .is_null(),
+    );
+
+    Query::update()
+        .table(Projects::Table)
+        .value(column, channel_setting(channel, config_value(backend, key), condition))
+        .to_owned()
+}
+
+// copies an environment override back, turned off channels become "-1" again
+fn restore_environment_override(
+    backend: DbBackend,
+    channel: &str,
+    key: &str,
+    column: ProjectEnvironments,
+) -> UpdateStatement {
+    let value = Expr::case(
+        Expr::col((
+            ProjectNotificationChannels::Table,
+            ProjectNotificationChannels::IsEnabled,
+        ))
+        .eq(0),
+        Expr::val("-1"),
+    )
+    .finally(config_value(backend, key));
+
+    let condition = Expr::col((
+        ProjectNotificationChannels::Table,
+        ProjectNotificationChannels::ProjectEnvironmentId,
+    ))
+    .equals((ProjectEnvironments::Table, ProjectEnvironments::ProjectEnvironmentId));
+
+    Query::update()
+        .table(ProjectEnvironments::Table)
+        .value(column, channel_setting(channel, value.into(), condition))
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectNotificationChannels::Table)
+                    .if_not_exists()
+                    .apply_defaults(manager)
+                    .col(
+                        ColumnDef::new(ProjectNotificationChannels::ProjectNotificationChannelId)
+                            .unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationChannels::ProjectId)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(unsigned_null(ProjectNotificationChannels::ProjectEnvironmentId))
+                    .col(
+                        ColumnDef::new(ProjectNotificationChannels::Channel)
+                            .string_len(40)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ProjectNotificationChannels::Config).text().not_null())
+                    .col(
+                        ColumnDef::new(ProjectNotificationChannels::IsEnabled)
+                            .tiny_integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationChannels::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_notification_channels_1")
+                            .from_col(ProjectNotificationChannels::ProjectId)
+                            .to(Projects::Table, Projects::ProjectId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_notification_channels_2")
+                            .from_col(ProjectNotificationChannels::ProjectEnvironmentId)
+                            .to(ProjectEnvironments::Table, ProjectEnvironments::ProjectEnvironmentId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_notification_channels_1")
+                    .table(ProjectNotificationChannels::Table)
+                    .col(ProjectNotificationChannels::ProjectId)
+                    .col(ProjectNotificationChannels::Channel)
+                    .to_owned(),
+            )
+            .await?;
+
+        // move the existing integrations over to the channels table
+        let slack_config = json_object(vec![
+            ("bot_token", Expr::col(Projects::SlackBotToken).into()),
+            ("channel", Expr::col(Projects::SlackChannel).into()),
+        ]);
+
+        manager
+            .exec_stmt(copy_channels("slack", slack_config, Projects::SlackBotToken)?)
+            .await?;
+
+        manager
+            .exec_stmt(copy_environment_overrides(
+                "slack",
+                "channel",
+                ProjectEnvironments::SlackChannel,
+            )?)
+            .await?;
+
+        for (channel, project_column, environment_column) in WEBHOOK_COLUMNS {
+            let config = json_object(vec![("url", Expr::col(project_column).into())]);
+
+            manager
+                .exec_stmt(copy_channels(channel, config, project_column)?)
+                .await?;
+
+            manager
+                .exec_stmt(copy_environment_overrides(channel, "url", environment_column)?)
+                .await?;
+        }
+
+        let project_columns = [
+            Projects::SlackBotToken,
+            Projects::SlackChannel,
+            Projects::SlackWebhook,
+            Projects::Webhook,
+            Projects::TeamsWebhook,
+        ];
+
+        for column in project_columns {
+            manager
+                .alter_table(Table::alter().table(Projects::Table).drop_column(column).to_owned())
+                .await?;
+        }
+
+        let environment_columns = [
+            ProjectEnvironments::SlackChannel,
+            ProjectEnvironments::SlackWebhook,
+            ProjectEnvironments::Webhook,
+            ProjectEnvironments::TeamsWebhook,
+        ];
+
+        for column in environment_columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectEnvironments::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let project_columns = [
+            string_len_null(Projects::SlackBotToken, 255),
+            string_len_null(Projects::SlackChannel, 255),
+            string_null(Projects::SlackWebhook),
+            string_null(Projects::Webhook),
+            string_null(Projects::TeamsWebhook),
+        ];
+
+        for mut column in project_columns {
+            manager
+                .alter_table(Table::alter().table(Projects::Table).add_column(&mut column).to_owned())
+                .await?;
+        }
+
+        let environment_columns = [
+            string_len_null(ProjectEnvironments::SlackChannel, 255),
+            string_len_null(ProjectEnvironments::SlackWebhook, 255),
+            string_len_null(ProjectEnvironments::Webhook, 255),
+            string_len_null(ProjectEnvironments::TeamsWebhook, 255),
+        ];
+
+        for mut column in environment_columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectEnvironments::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        // copy the integrations back, channels added since have no columns to go to
+        let backend = manager.get_database_backend();
+
+        manager
+            .exec_stmt(restore_channel(backend, "slack", "bot_token", Projects::SlackBotToken))
+            .await?;
+
+        manager
+            .exec_stmt(restore_channel(backend, "slack", "channel", Projects::SlackChannel))
+            .await?;
+
+        manager
+            .exec_stmt(restore_environment_override(
+                backend,
+                "slack",
+                "channel",
+                ProjectEnvironments::SlackChannel,
+            ))
+            .await?;
+
+        for (channel, project_column, environment_column) in WEBHOOK_COLUMNS {
+            manager
+                .exec_stmt(restore_channel(backend, channel, "url", project_column))
+                .await?;
+
+            manager
+                .exec_stmt(restore_environment_override(
+                    backend,
+                    channel,
+                    "url",
+                    environment_column,
+                ))
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(ProjectNotificationChannels::Table).to_owned())
+            .await
+    }
+}
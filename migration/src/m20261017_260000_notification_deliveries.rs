@@ -0,0 +1,187 @@
+use sea_orm_migration::prelude::*;
+
+use crate::TableDefaults;
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    ProjectId,
+}
+
+#[derive(DeriveIden)]
+enum ProjectReports {
+    Table,
+    ProjectReportId,
+}
+
+#[derive(DeriveIden)]
+enum ProjectNotificationDeliveries {
+    Table,
+    ProjectNotificationDeliveryId,
+    ProjectId,
+    ProjectReportId,
+    ProjectReportEventId,
+    ProjectReportMergeId,
+    ProjectEnvironmentId,
+    UserId,
+    Channel,
+    ReportStatus,
+    Status,
+    Attempts,
+    AvailableAt,
+    ResponseCode,
+    Error,
+    Created,
+    Delivered,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectNotificationDeliveries::Table)
+                    .if_not_exists()
+                    .apply_defaults(manager)
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::ProjectNotificationDeliveryId)
+                            .unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::ProjectId)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::ProjectReportId)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    // closing an incident only needs the report, its events might be gone by then
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::ProjectReportEventId)
+                            .unsigned()
+                            .null(),
+                    )
+                    // set when closing the incident of a report merged into another one
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::ProjectReportMergeId)
+                            .unsigned()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::ProjectEnvironmentId)
+                            .unsigned()
+                            .null(),
+                    )
+                    // set for deliveries to a single user, like emails
+                    .col(ColumnDef::new(ProjectNotificationDeliveries::UserId).unsigned().null())
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::Channel)
+                            .string_len(40)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::ReportStatus)
+                            .string_len(80)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::Attempts)
+                            .unsigned()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::AvailableAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::ResponseCode)
+                            .unsigned()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(ProjectNotificationDeliveries::Error).text().null())
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectNotificationDeliveries::Delivered)
+                            .date_time()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_notification_deliveries_1")
+                            .from_col(ProjectNotificationDeliveries::ProjectId)
+                            .to(Projects::Table, Projects::ProjectId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_notification_deliveries_2")
+                            .from_col(ProjectNotificationDeliveries::ProjectReportId)
+                            .to(ProjectReports::Table, ProjectReports::ProjectReportId)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_notification_deliveries_1")
+                    .table(ProjectNotificationDeliveries::Table)
+                    .col(ProjectNotificationDeliveries::Status)
+                    .col(ProjectNotificationDeliveries::AvailableAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_notification_deliveries_2")
+                    .table(ProjectNotificationDeliveries::Table)
+                    .col(ProjectNotificationDeliveries::ProjectId)
+                    .col(ProjectNotificationDeliveries::ProjectNotificationDeliveryId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(ProjectNotificationDeliveries::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
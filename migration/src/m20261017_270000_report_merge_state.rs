@@ -0,0 +1,67 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveIden)]
+enum ProjectReportMerges {
+    Table,
+    ResolvedInVersion,
+    IsIgnored,
+    IgnoreCondition,
+    IgnoredUntil,
+    IgnoredEventsLeft,
+    IgnoredInVersion,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sqlite can only add one column per statement
+        let columns = [
+            string_len_null(ProjectReportMerges::ResolvedInVersion, 64),
+            tiny_integer(ProjectReportMerges::IsIgnored).default(0).to_owned(),
+            string_len_null(ProjectReportMerges::IgnoreCondition, 16),
+            date_time_null(ProjectReportMerges::IgnoredUntil),
+            unsigned_null(ProjectReportMerges::IgnoredEventsLeft),
+            string_len_null(ProjectReportMerges::IgnoredInVersion, 64),
+        ];
+
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectReportMerges::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            ProjectReportMerges::ResolvedInVersion,
+            ProjectReportMerges::IsIgnored,
+            ProjectReportMerges::IgnoreCondition,
+            ProjectReportMerges::IgnoredUntil,
+            ProjectReportMerges::IgnoredEventsLeft,
+            ProjectReportMerges::IgnoredInVersion,
+        ];
+
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProjectReportMerges::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+// keep the serialized frames well below the 64KB limit of mysql TEXT columns
+const MAX_FRAMES: usize = 64;
+const MAX_SYMBOL_LEN: usize = 300;
+
+// frames from these crates are never considered application code
+const LIBRARY_CRATES: &[&str] = &[
+    "std",
+    "core",
+    "alloc",
+    "backtrace",
+    "panic_unwind",
+    "panic_abort",
+    "test",
+    "tokio",
+    "tokio_util",
+    "futures",
+    "futures_core",
+    "futures_util",
+    "futures_executor",
+    "dontpanic",
+];
+
+// `0: symbol`, with RUST_BACKTRACE=full `0: 0x55d0e5c5b8d5 - symbol`
+static FRAME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(\d+):\s+(?:0x[0-9a-fA-F]+\s+-\s+)?(.+?)(?:\s+\(0x[0-9a-fA-F]+\))?\s*$").unwrap()
+});
+static LOCATION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*at\s+(.+?)(?::(\d+))?(?::(\d+))?\s*$").unwrap());
+static INLINED_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s+([^\s].*?)\s*$").unwrap());
+static HASH_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"::h[0-9a-f]{16}$").unwrap());
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub index: u32,
+    pub symbol: String,
+    #[serde(rename = "crate")]
+    pub crate_name: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub in_app: bool,
+}
+
+/// Parses the Display output of `std::backtrace::Backtrace` and the `backtrace` crate,
+/// including the `RUST_BACKTRACE=full` form with frame addresses.
+///
+/// Frames inlined into another one share its index. Lines that are not part of a frame
+/// (headers, notes about omitted frames) are skipped.
+pub fn parse(trace: &str) -> Vec<Frame> {
+    let mut frames: Vec<Frame> = vec![];
+    let mut current_index = None;
+
+    for line in trace.lines() {
+        if let Some(caps) = FRAME_RE.captures(line) {
+            let Ok(index) = caps[1].parse() else {
+                continue;
+            };
+
+            current_index = Some(index);
+            frames.push(new_frame(index, &caps[2]));
+        } else if let Some(caps) = LOCATION_RE.captures(line) {
+            let Some(frame) = frames.last_mut() else {
+                continue;
+            };
+
+            frame.file = Some(caps[1].to_string());
+            frame.line = caps.get(2).and_then(|l| l.as_str().parse().ok());
+            frame.column = caps.get(3).and_then(|c| c.as_str().parse().ok());
+            frame.in_app = is_in_app(frame.crate_name.as_deref(), frame.file.as_deref());
+        } else if let Some(caps) = INLINED_RE.captures(line) {
+            let Some(index) = current_index else {
+                continue;
+            };
+
+            frames.push(new_frame(index, &caps[1]));
+        } else {
+            current_index = None;
+        }
+
+        if frames.len() >= MAX_FRAMES {
+            break;
+        }
+    }
+
+    frames
+}
+
+fn new_frame(index: u32, raw_symbol: &str) -> Frame {
+    let symbol = strip_hash(raw_symbol.trim());
+    let symbol = symbol.chars().take(MAX_SYMBOL_LEN).collect::<String>();
+    let crate_name = crate_name(&symbol);
+    let in_app = is_in_app(crate_name.as_deref(), None);
+
+    Frame {
+        index,
+        symbol,
+        crate_name,
+        file: None,
+        line: None,
+        column: None,
+        in_app,
+    }
+}
+
+// remove the ::h0123456789abcdef suffix from legacy mangled symbols
+fn strip_hash(symbol: &str) -> String {
+    HASH_RE.replace(symbol, "").into_owned()
+}
+
+/// The first path segment of a symbol, `<alloc::boxed::Box<F> as Fn>::call` yields `alloc`
+fn crate_name(symbol: &str) -> Option<String> {
+    let path = symbol.trim_start_matches('<');
+    let (first, _) = path.split_once("::")?;
+
+    let valid = !first.is_empty() && first.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    valid.then(|| first.to_string())
+}
+
+fn is_in_app(crate_name: Option<&str>, file: Option<&str>) -> bool {
+    let Some(crate_name) = crate_name else {
+        return false;
+    };
+
+    if LIBRARY_CRATES.contains(&crate_name) || crate_name.starts_with("__rust") {
+        return false;
+    }
+
+    // sources of the standard library and dependencies downloaded by cargo
+    if let Some(file) = file {
+        let file = file.replace('\\', "/");
+
+        if file.starts_with("/rustc/")
+            || file.contains("/.cargo/registry/")
+            || file.contains("/.cargo/git/")
+            || file.contains("/rustlib/src/")
+        {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_std_backtrace() {
+        let trace = r#"   0: rust_begin_unwind
+             at /rustc/90b35a6239c3d8bdabc530a6a0816f7ff89a0aaf/library/std/src/panicking.rs:665:5
+   1: core::panicking::panic_fmt
+             at /rustc/90b35a6239c3d8bdabc530a6a0816f7ff89a0aaf/library/core/src/panicking.rs:74:14
+   2: <tower::util::map_err::MapErr<S,F> as tower_service::Service<R>>::call
+             at /home/user/.cargo/registry/src/index.crates.io-6f17d22bba15001f/tower-0.4.13/src/util/map_err.rs:60:9
+   3: my_app::handlers::users::create::h0123456789abcdef
+             at ./src/handlers/users.rs:42:18
+      my_app::handlers::users::validate
+             at ./src/handlers/users.rs:12
+   4: main
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace."#;
+
+        let frames = parse(trace);
+
+        assert_eq!(frames.len(), 6);
+
+        assert_eq!(frames[0].symbol, "rust_begin_unwind");
+        assert_eq!(frames[0].crate_name, None);
+        assert!(!frames[0].in_app);
+
+        assert_eq!(frames[1].crate_name.as_deref(), Some("core"));
+        assert_eq!(frames[1].line, Some(74));
+        assert_eq!(frames[1].column, Some(14));
+        assert!(!frames[1].in_app);
+
+        assert_eq!(frames[2].crate_name.as_deref(), Some("tower"));
+        assert!(!frames[2].in_app);
+
+        assert_eq!(frames[3].index, 3);
+        assert_eq!(frames[3].symbol, "my_app::handlers::users::create");
+        assert_eq!(frames[3].crate_name.as_deref(), Some("my_app"));
+        assert_eq!(frames[3].file.as_deref(), Some("./src/handlers/users.rs"));
+        assert!(frames[3].in_app);
+
+        assert_eq!(frames[4].index, 3);
+        assert_eq!(frames[4].symbol, "my_app::handlers::users::validate");
+        assert_eq!(frames[4].line, Some(12));
+        assert_eq!(frames[4].column, None);
+        assert!(frames[4].in_app);
+
+        assert_eq!(frames[5].index, 4);
+        assert_eq!(frames[5].symbol, "main");
+        assert!(!frames[5].in_app);
+    }
+
+    #[test]
+    fn test_parse_full_backtrace() {
+        let trace = r#"   0:     0x55d0e5c5b8d5 - std::backtrace_rs::backtrace::libunwind::trace::h5a5b8284f2d0c266
+                               at /rustc/90b35a6239c3d8bdabc530a6a0816f7ff89a0aaf/library/std/src/../../backtrace/src/backtrace/libunwind.rs:116:5
+   1:     0x55d0e5c6a1f2 - my_app::handlers::users::create::h0123456789abcdef
+                               at ./src/handlers/users.rs:42:18
+   2:     0x55d0e5c6b3a0 - main
+   3:     0x7f1c2e229d90 - <unknown>"#;
+
+        let frames = parse(trace);
+
+        assert_eq!(frames.len(), 4);
+
+        assert_eq!(frames[0].symbol, "std::backtrace_rs::backtrace::libunwind::trace");
+        assert_eq!(frames[0].crate_name.as_deref(), Some("std"));
+        assert_eq!(frames[0].line, Some(116));
+        assert!(!frames[0].in_app);
+
+        assert_eq!(frames[1].index, 1);
+        assert_eq!(frames[1].symbol, "my_app::handlers::users::create");
+        assert_eq!(frames[1].file.as_deref(), Some("./src/handlers/users.rs"));
+        assert_eq!(frames[1].column, Some(18));
+        assert!(frames[1].in_app);
+
+        assert_eq!(frames[2].symbol, "main");
+        assert_eq!(frames[3].symbol, "<unknown>");
+        assert_eq!(frames[3].crate_name, None);
+    }
+}
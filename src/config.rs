@@ -36,6 +36,14 @@ pub struct Config {
 
     pub organization_requests_limit: Option<u32>,
 
+    pub ingress_workers: u32,
+    pub ingress_max_payload_size: usize,
+
+    pub event_retention_count: u32,
+    pub event_retention_days: u32,
+
+    pub spike_protection_multiplier: u32,
+
     pub registration_enabled: bool,
     pub require_email_verification: bool,
 }
@@ -80,6 +88,16 @@ impl Config {
 
         let email_url = get_var("EMAIL_URL").ok();
 
+        let ingress_workers: u32 = get_var("INGRESS_WORKERS")
+            .ok()
+            .map(|workers| workers.parse())
+            .transpose()?
+            .unwrap_or(4);
+
+        if ingress_workers == 0 {
+            anyhow::bail!("INGRESS_WORKERS must be at least 1");
+        }
+
         Ok(Self {
             bind_addr: get_var("BIND_ADDRESS")
                 .ok()
@@ -107,6 +125,27 @@ impl Config {
                 .ok()
                 .map(|limit| limit.parse())
                 .transpose()?,
+            ingress_workers,
+            ingress_max_payload_size: get_var("INGRESS_MAX_PAYLOAD_SIZE")
+                .ok()
+                .map(|size| size.parse())
+                .transpose()?
+                .unwrap_or(4 * 1024 * 1024),
+            event_retention_count: get_var("EVENT_RETENTION_COUNT")
+                .ok()
+                .map(|count| count.parse())
+                .transpose()?
+                .unwrap_or(5),
+            event_retention_days: get_var("EVENT_RETENTION_DAYS")
+                .ok()
+                .map(|days| days.parse())
+                .transpose()?
+                .unwrap_or(0),
+            spike_protection_multiplier: get_var("SPIKE_PROTECTION_MULTIPLIER")
+                .ok()
+                .map(|multiplier| multiplier.parse())
+                .transpose()?
+                .unwrap_or(10),
             registration_enabled: get_bool_var("REGISTRATION_ENABLED")?.unwrap_or(true),
             require_email_verification: get_bool_var("REQUIRE_EMAIL_VERIFICATION")?.unwrap_or(email_url.is_some()),
             email_url,
@@ -2,18 +2,19 @@ use anyhow::Result;
 use chrono::prelude::*;
 
 use lettre::AsyncTransport;
-use sea_orm::{prelude::*, JoinType, QuerySelect};
-use sea_orm::{ActiveValue, IntoActiveModel, QueryOrder, TryIntoModel};
+use sea_orm::{prelude::*, sea_query::Query, JoinType, QuerySelect};
+use sea_orm::{ActiveValue, IntoActiveModel, QueryOrder, TransactionTrait, TryIntoModel};
 
 use tokio::join;
 use tokio_schedule::{every, Job};
 
-use crate::notifications::{Notification, ReportStatus};
+use crate::notifications::{outbox, Notification, ReportStatus};
 use crate::AppContext;
 
 use crate::entity::prelude::*;
 use crate::entity::{
-    organization_stats, organization_users, organizations, project_report_events, project_report_stats, users,
+    organization_stats, organization_users, organizations, project_report_events, project_report_stats,
+    project_reports, users,
 };
 
 pub async fn run_command(ctx: AppContext<'_>, cmd: &str) -> Result<()> {
@@ -21,6 +22,10 @@ pub async fn run_command(ctx: AppContext<'_>, cmd: &str) -> Result<()> {
         "disable-depleted-orgs" => disable_depleted_orgs(ctx).await,
         "notify-spiking" => notify_spiking_reports(ctx).await,
         "notify-limits" => notify_organization_limits(ctx).await,
+        "requeue-dead-events" => requeue_dead_events(ctx).await,
+        "prune-dead-events" => prune_dead_events(ctx).await,
+        "unignore-expired" => unignore_expired_reports(ctx).await,
+        "enforce-retention" => enforce_retention(ctx).await,
         _ => Err(anyhow::anyhow!("Unknown command")),
     }
 }
@@ -44,7 +49,144 @@ pub async fn cronjobs(ctx: AppContext<'_>) {
         }
     });
 
-    join!(disable_depleted_orgs, spiking_reports, organization_limits);
+    let retention = every(10).minutes().perform(|| async {
+        if let Err(e) = enforce_retention(ctx.clone()).await {
+            log::error!("Error enforcing event retention: {}", e);
+        }
+    });
+
+    let dead_events = every(1).hour().perform(|| async {
+        if let Err(e) = prune_dead_events(ctx.clone()).await {
+            log::error!("Error pruning dead events: {}", e);
+        }
+    });
+
+    let expired_ignores = every(1).minutes().perform(|| async {
+        if let Err(e) = unignore_expired_reports(ctx.clone()).await {
+            log::error!("Error unignoring reports: {}", e);
+        }
+    });
+
+    join!(
+        disable_depleted_orgs,
+        spiking_reports,
+        organization_limits,
+        retention,
+        dead_events,
+        expired_ignores
+    );
+}
+
+/// Deletes events beyond the retention settings of their project
+pub async fn enforce_retention(ctx: AppContext<'_>) -> Result<()> {
+    let projects = Projects::find().all(&ctx.db).await?;
+    let mut deleted = 0;
+
+    for project in projects {
+        let project_reports = Query::select()
+            .column(project_reports::Column::ProjectReportId)
+            .from(ProjectReports)
+            .and_where(project_reports::Column::ProjectId.eq(project.project_id))
+            .to_owned();
+
+        let retention_days = project.retention_days.unwrap_or(ctx.config.event_retention_days);
+
+        if retention_days > 0 {
+            let cutoff = Utc::now().naive_utc() - chrono::Duration::days(retention_days.into());
+
+            let res = ProjectReportEvents::delete_many()
+                .filter(project_report_events::Column::ProjectReportId.in_subquery(project_reports.clone()))
+                .filter(project_report_events::Column::Created.lt(cutoff))
+                .exec(&ctx.db)
+                .await?;
+
+            deleted += res.rows_affected;
+        }
+
+        let retention_events = project.retention_events.unwrap_or(ctx.config.event_retention_count);
+
+        if retention_events == 0 {
+            continue;
+        }
+
+        let over_limit: Vec<u32> = ProjectReportEvents::find()
+            .select_only()
+            .column(project_report_events::Column::ProjectReportId)
+            .filter(project_report_events::Column::ProjectReportId.in_subquery(project_reports))
+            .group_by(project_report_events::Column::ProjectReportId)
+            .having(Expr::expr(project_report_events::Column::ProjectReportEventId.count()).gt(retention_events))
+            .into_tuple()
+            .all(&ctx.db)
+            .await?;
+
+        for report_id in over_limit {
+            // the oldest event that is still kept
+            let oldest_kept: Option<u32> = ProjectReportEvents::find()
+                .select_only()
+                .column(project_report_events::Column::ProjectReportEventId)
+                .filter(project_report_events::Column::ProjectReportId.eq(report_id))
+                .order_by_desc(project_report_events::Column::ProjectReportEventId)
+                .offset(u64::from(retention_events) - 1)
+                .into_tuple()
+                .one(&ctx.db)
+                .await?;
+
+            let Some(oldest_kept) = oldest_kept else {
+                continue;
+            };
+
+            let res = ProjectReportEvents::delete_many()
+                .filter(project_report_events::Column::ProjectReportId.eq(report_id))
+                .filter(project_report_events::Column::ProjectReportEventId.lt(oldest_kept))
+                .exec(&ctx.db)
+                .await?;
+
+            deleted += res.rows_affected;
+        }
+    }
+
+    log::info!("Deleted {} events past their retention", deleted);
+
+    Ok(())
+}
+
+pub async fn requeue_dead_events(ctx: AppContext<'_>) -> Result<()> {
+    let count = crate::queue::requeue_dead(&ctx).await?;
+
+    log::info!("Moved {} dead events back to the ingress queue", count);
+
+    Ok(())
+}
+
+pub async fn prune_dead_events(ctx: AppContext<'_>) -> Result<()> {
+    let count = crate::queue::prune_dead(&ctx).await?;
+
+    log::info!("Deleted {} dead events past their retention", count);
+
+    Ok(())
+}
+
+/// Reports ignored for a while are shown again once the time is up, even without new events
+pub async fn unignore_expired_reports(ctx: AppContext<'_>) -> Result<()> {
+    let res = ProjectReports::update_many()
+        .col_expr(project_reports::Column::IsIgnored, Expr::value(0))
+        .col_expr(
+            project_reports::Column::IgnoreCondition,
+            Expr::value(Option::<String>::None),
+        )
+        .col_expr(
+            project_reports::Column::IgnoredUntil,
+            Expr::value(Option::<NaiveDateTime>::None),
+        )
+        .filter(project_reports::Column::IsIgnored.gt(0))
+        .filter(project_reports::Column::IgnoreCondition.eq("time"))
+        .filter(project_reports::Column::IgnoredUntil.lte(Utc::now().naive_utc()))
+        .exec(&ctx.db)
+        .await?;
+
+    log::info!("Unignored {} reports", res.rows_affected);
+
+    Ok(())
 }
 
 pub async fn notify_spiking_reports(ctx: AppContext<'_>) -> Result<()> {
@@ -82,49 +224,73 @@ pub async fn notify_spiking_reports(ctx: AppContext<'_>) -> Result<()> {
         let diff_percent = (diff as f64 / last_hour_report.count as f64) * 100.0;
 
         if diff_percent > 101.0 {
+            let txn = ctx.db.begin().await?;
+
             let mut report_stat = report_stat.clone().into_active_model();
             report_stat.spiking = ActiveValue::set(true as i8);
-            let report_stat = report_stat.save(&ctx.db).await?.try_into_model()?;
+            let report_stat = report_stat.save(&txn).await?.try_into_model()?;
 
-            let Some(report) = report_stat.find_related(ProjectReports).one(&ctx.db).await? else {
-                continue;
-            };
+            let notification = spike_notification(&txn, &report_stat, diff_percent.round() as u32).await?;
 
-            let Some(project) = report.find_related(Projects).one(&ctx.db).await? else {
-                continue;
-            };
+            if let Some(notification) = &notification {
+                outbox::enqueue(&txn, notification).await?;
+            }
 
-            let event = report
-                .find_related(ProjectReportEvents)
-                .order_by_desc(project_report_events::Column::ProjectReportEventId)
-                .one(&ctx.db)
-                .await?;
+            txn.commit().await?;
 
-            let Some(event) = event else {
+            let Some(notification) = notification else {
                 continue;
             };
 
-            let environment = report.find_related(ProjectEnvironments).one(&ctx.db).await?;
-
-            let res = ctx.notifications.send(Notification {
-                status: Some(ReportStatus::Spiking {
-                    percentage: diff_percent.round() as u32,
-                }),
-                project,
-                event,
-                report,
-                environment,
-            });
+            ctx.notification_queue.notify_waiters();
 
-            if let Err(e) = res {
-                log::error!("Error sending notification: {:?}", e);
-            }
+            // there might be no clients listening for live updates
+            let _ = ctx.notifications.send(notification);
         }
     }
 
     Ok(())
 }
 
+/// Notification about a spiking report, unless the report is ignored or has no events left
+async fn spike_notification(
+    db: &impl ConnectionTrait,
+    report_stat: &project_report_stats::Model,
+    percentage: u32,
+) -> Result<Option<Notification>> {
+    let Some(report) = report_stat.find_related(ProjectReports).one(db).await? else {
+        return Ok(None);
+    };
+
+    if report.is_ignored > 0 {
+        return Ok(None);
+    }
+
+    let Some(project) = report.find_related(Projects).one(db).await? else {
+        return Ok(None);
+    };
+
+    let event = report
+        .find_related(ProjectReportEvents)
+        .order_by_desc(project_report_events::Column::ProjectReportEventId)
+        .one(db)
+        .await?;
+
+    let Some(event) = event else {
+        return Ok(None);
+    };
+
+    let environment = report.find_related(ProjectEnvironments).one(db).await?;
+
+    Ok(Some(Notification {
+        status: Some(ReportStatus::Spiking { percentage }),
+        project,
+        event,
+        report,
+        environment,
+    }))
+}
+
 pub async fn notify_organization_limits(ctx: AppContext<'_>) -> Result<()> {
     let Some(mailer) = ctx.mailer.as_ref() else {
         log::warn!("Mailer is not configured");
@@ -245,3 +411,106 @@ pub async fn disable_depleted_orgs(ctx: AppContext<'_>) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::projects;
+
+    #[actix_web::test]
+    async fn test_enforce_retention() {
+        let ctx = AppContext::testing().await.unwrap();
+
+        let project = projects::ActiveModel {
+            organization_id: ActiveValue::set(1),
+            name: ActiveValue::set("Retention Project".into()),
+            api_key: ActiveValue::set("retention".into()),
+            retention_events: ActiveValue::set(Some(3)),
+            retention_days: ActiveValue::set(Some(7)),
+            ..Default::default()
+        }
+        .insert(&ctx.db)
+        .await
+        .unwrap();
+
+        let report = project_reports::ActiveModel {
+            project_id: ActiveValue::set(project.project_id),
+            title: ActiveValue::set("Retention".into()),
+            uid: ActiveValue::set("retention".into()),
+            last_seen: ActiveValue::set(Utc::now().naive_utc()),
+            ..Default::default()
+        }
+        .insert(&ctx.db)
+        .await
+        .unwrap();
+
+        // four recent events followed by two that are past the retention days
+        let now = Utc::now().naive_utc();
+        let created = [0, 0, 0, 0, 10, 10].map(|days_ago| now - chrono::Duration::days(days_ago));
+
+        for created in created {
+            project_report_events::ActiveModel {
+                project_report_id: ActiveValue::set(report.project_report_id),
+                created: ActiveValue::set(Some(created)),
+                ..Default::default()
+            }
+            .insert(&ctx.db)
+            .await
+            .unwrap();
+        }
+
+        enforce_retention(ctx.clone()).await.unwrap();
+
+        let events = report.find_related(ProjectReportEvents).all(&ctx.db).await.unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|e| e.created == Some(now)));
+    }
+
+    #[actix_web::test]
+    async fn test_unignore_expired_reports() {
+        let ctx = AppContext::testing().await.unwrap();
+
+        let project = projects::ActiveModel {
+            organization_id: ActiveValue::set(1),
+            name: ActiveValue::set("Ignore Project".into()),
+            api_key: ActiveValue::set("ignore".into()),
+            ..Default::default()
+        }
+        .insert(&ctx.db)
+        .await
+        .unwrap();
+
+        let now = Utc::now().naive_utc();
+
+        // one ignore ran out, the other one didn't
+        for (uid, minutes) in [("expired", -5), ("ignored", 5)] {
+            project_reports::ActiveModel {
+                project_id: ActiveValue::set(project.project_id),
+                title: ActiveValue::set(uid.into()),
+                uid: ActiveValue::set(uid.into()),
+                last_seen: ActiveValue::set(now),
+                is_ignored: ActiveValue::set(1),
+                ignore_condition: ActiveValue::set(Some("time".into())),
+                ignored_until: ActiveValue::set(Some(now + chrono::Duration::minutes(minutes))),
+                ..Default::default()
+            }
+            .insert(&ctx.db)
+            .await
+            .unwrap();
+        }
+
+        unignore_expired_reports(ctx.clone()).await.unwrap();
+
+        let ignored: Vec<String> = ProjectReports::find()
+            .filter(project_reports::Column::IsIgnored.gt(0))
+            .all(&ctx.db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|report| report.uid)
+            .collect();
+
+        assert_eq!(ignored, ["ignored"]);
+    }
+}
@@ -7,9 +7,17 @@ pub mod organization_stats;
 pub mod organization_users;
 pub mod organizations;
 pub mod project_environments;
+pub mod project_grouping_rules;
+pub mod project_inbound_filters;
+pub mod project_keys;
+pub mod project_notification_channels;
+pub mod project_notification_deliveries;
+pub mod project_releases;
 pub mod project_report_events;
+pub mod project_report_merges;
 pub mod project_report_stats;
 pub mod project_reports;
 pub mod project_user_settings;
 pub mod projects;
+pub mod queued_events;
 pub mod users;
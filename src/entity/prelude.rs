@@ -5,9 +5,17 @@ pub use super::organization_stats::Entity as OrganizationStats;
 pub use super::organization_users::Entity as OrganizationUsers;
 pub use super::organizations::Entity as Organizations;
 pub use super::project_environments::Entity as ProjectEnvironments;
+pub use super::project_grouping_rules::Entity as ProjectGroupingRules;
+pub use super::project_inbound_filters::Entity as ProjectInboundFilters;
+pub use super::project_keys::Entity as ProjectKeys;
+pub use super::project_notification_channels::Entity as ProjectNotificationChannels;
+pub use super::project_notification_deliveries::Entity as ProjectNotificationDeliveries;
+pub use super::project_releases::Entity as ProjectReleases;
 pub use super::project_report_events::Entity as ProjectReportEvents;
+pub use super::project_report_merges::Entity as ProjectReportMerges;
 pub use super::project_report_stats::Entity as ProjectReportStats;
 pub use super::project_reports::Entity as ProjectReports;
 pub use super::project_user_settings::Entity as ProjectUserSettings;
 pub use super::projects::Entity as Projects;
+pub use super::queued_events::Entity as QueuedEvents;
 pub use super::users::Entity as Users;
@@ -10,14 +10,17 @@ pub struct Model {
     pub project_environment_id: u32,
     pub project_id: u32,
     pub name: String,
-    pub slack_channel: Option<String>,
-    pub slack_webhook: Option<String>,
-    pub webhook: Option<String>,
-    pub teams_webhook: Option<String>,
+    pub is_hidden: i8,
+    pub grouping_name: Option<String>,
+    pub merged_into_id: Option<u32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::project_notification_channels::Entity")]
+    ProjectNotificationChannels,
+    #[sea_orm(has_many = "super::project_report_merges::Entity")]
+    ProjectReportMerges,
     #[sea_orm(has_many = "super::project_reports::Entity")]
     ProjectReports,
     #[sea_orm(
@@ -30,6 +33,18 @@ pub enum Relation {
     Projects,
 }
 
+impl Related<super::project_notification_channels::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectNotificationChannels.def()
+    }
+}
+
+impl Related<super::project_report_merges::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectReportMerges.def()
+    }
+}
+
 impl Related<super::project_reports::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProjectReports.def()
@@ -14,6 +14,24 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub log: Option<String>,
     pub created: Option<DateTime>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub frames: Option<String>,
+    pub project_report_merge_id: Option<u32>,
+    pub os: Option<String>,
+    pub arch: Option<String>,
+    pub version: Option<String>,
+    pub thread_id: Option<String>,
+    pub thread_name: Option<String>,
+    pub location_file: Option<String>,
+    pub location_line: Option<u32>,
+    pub location_column: Option<u32>,
+    pub received: Option<DateTime>,
+    pub client_ip_hash: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub tags: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub context: Option<String>,
+    pub project_key_id: Option<u32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
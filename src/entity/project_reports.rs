@@ -17,6 +17,14 @@ pub struct Model {
     pub created: Option<DateTime>,
     #[sea_orm(unique)]
     pub uid: String,
+    pub first_version: Option<String>,
+    pub last_version: Option<String>,
+    pub resolved_in_version: Option<String>,
+    pub is_ignored: i8,
+    pub ignore_condition: Option<String>,
+    pub ignored_until: Option<DateTime>,
+    pub ignored_events_left: Option<u32>,
+    pub ignored_in_version: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -29,8 +37,12 @@ pub enum Relation {
         on_delete = "SetNull"
     )]
     ProjectEnvironments,
+    #[sea_orm(has_many = "super::project_notification_deliveries::Entity")]
+    ProjectNotificationDeliveries,
     #[sea_orm(has_many = "super::project_report_events::Entity")]
     ProjectReportEvents,
+    #[sea_orm(has_many = "super::project_report_merges::Entity")]
+    ProjectReportMerges,
     #[sea_orm(has_many = "super::project_report_stats::Entity")]
     ProjectReportStats,
     #[sea_orm(
@@ -49,12 +61,24 @@ impl Related<super::project_environments::Entity> for Entity {
     }
 }
 
+impl Related<super::project_notification_deliveries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectNotificationDeliveries.def()
+    }
+}
+
 impl Related<super::project_report_events::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProjectReportEvents.def()
     }
 }
 
+impl Related<super::project_report_merges::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectReportMerges.def()
+    }
+}
+
 impl Related<super::project_report_stats::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProjectReportStats.def()
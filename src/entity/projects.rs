@@ -12,12 +12,15 @@ pub struct Model {
     pub name: String,
     #[sea_orm(unique)]
     pub api_key: String,
-    pub slack_bot_token: Option<String>,
-    pub slack_channel: Option<String>,
     pub created: DateTime,
-    pub slack_webhook: Option<String>,
-    pub webhook: Option<String>,
-    pub teams_webhook: Option<String>,
+    pub grouping: String,
+    pub retention_events: Option<u32>,
+    pub retention_days: Option<u32>,
+    pub rate_limit: Option<u32>,
+    pub report_rate_limit: Option<u32>,
+    pub spike_protection: i8,
+    pub auto_create_environments: i8,
+    pub default_environment_id: Option<u32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -32,10 +35,24 @@ pub enum Relation {
     Organizations,
     #[sea_orm(has_many = "super::project_environments::Entity")]
     ProjectEnvironments,
+    #[sea_orm(has_many = "super::project_grouping_rules::Entity")]
+    ProjectGroupingRules,
+    #[sea_orm(has_many = "super::project_inbound_filters::Entity")]
+    ProjectInboundFilters,
+    #[sea_orm(has_many = "super::project_keys::Entity")]
+    ProjectKeys,
+    #[sea_orm(has_many = "super::project_notification_channels::Entity")]
+    ProjectNotificationChannels,
+    #[sea_orm(has_many = "super::project_notification_deliveries::Entity")]
+    ProjectNotificationDeliveries,
+    #[sea_orm(has_many = "super::project_releases::Entity")]
+    ProjectReleases,
     #[sea_orm(has_many = "super::project_reports::Entity")]
     ProjectReports,
     #[sea_orm(has_many = "super::project_user_settings::Entity")]
     ProjectUserSettings,
+    #[sea_orm(has_many = "super::queued_events::Entity")]
+    QueuedEvents,
 }
 
 impl Related<super::organizations::Entity> for Entity {
@@ -50,6 +67,42 @@ impl Related<super::project_environments::Entity> for Entity {
     }
 }
 
+impl Related<super::project_grouping_rules::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectGroupingRules.def()
+    }
+}
+
+impl Related<super::project_inbound_filters::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectInboundFilters.def()
+    }
+}
+
+impl Related<super::project_keys::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectKeys.def()
+    }
+}
+
+impl Related<super::project_notification_channels::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectNotificationChannels.def()
+    }
+}
+
+impl Related<super::project_notification_deliveries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectNotificationDeliveries.def()
+    }
+}
+
+impl Related<super::project_releases::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectReleases.def()
+    }
+}
+
 impl Related<super::project_reports::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProjectReports.def()
@@ -62,6 +115,12 @@ impl Related<super::project_user_settings::Entity> for Entity {
     }
 }
 
+impl Related<super::queued_events::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::QueuedEvents.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         super::project_user_settings::Relation::Users.def()
@@ -21,6 +21,7 @@ pub struct ErrorMessage {
 pub enum Error {
     NotFound,
     LoginRequired,
+    PayloadTooLarge(usize),
     User(ErrorMessage),
     Fields(HashMap<String, ErrorMessage>),
     #[serde(skip)]
@@ -59,6 +60,7 @@ impl fmt::Display for Error {
             Self::NotFound => write!(f, "Not Found"),
             Self::User(msg) => write!(f, "{}", msg.message),
             Self::LoginRequired => write!(f, "Unauthorized"),
+            Self::PayloadTooLarge(limit) => write!(f, "Request body is larger than the allowed {} bytes", limit),
             Self::Fields(_) => write!(f, "Bad Request"),
             Self::Internal(_) => write!(f, "An internal error occurred. Please try again later."),
         }
@@ -92,6 +94,13 @@ impl actix_web::error::ResponseError for Error {
             }));
         }
 
+        if let Self::PayloadTooLarge(_) = self {
+            return builder.json(Self::User(ErrorMessage {
+                r#type: Some("payload_too_large".into()),
+                message: self.to_string(),
+            }));
+        }
+
         builder.json(self)
     }
 
@@ -99,6 +108,7 @@ impl actix_web::error::ResponseError for Error {
         match *self {
             Self::NotFound => StatusCode::NOT_FOUND,
             Self::LoginRequired => StatusCode::UNAUTHORIZED,
+            Self::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
             Self::User(_) => StatusCode::BAD_REQUEST,
             Self::Fields(_) => StatusCode::BAD_REQUEST,
             Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
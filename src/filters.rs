@@ -0,0 +1,176 @@
+use std::str::FromStr;
+
+use regex::Regex;
+use sea_orm::prelude::*;
+
+use crate::entity::prelude::*;
+use crate::entity::project_inbound_filters;
+use crate::grouping::compile_pattern;
+use crate::releases;
+
+/// Part of an event an inbound filter is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterField {
+    Environment,
+    Version,
+    Os,
+    Title,
+    Log,
+}
+
+impl FilterField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Environment => "environment",
+            Self::Version => "version",
+            Self::Os => "os",
+            Self::Title => "title",
+            Self::Log => "log",
+        }
+    }
+}
+
+impl FromStr for FilterField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "environment" => Ok(Self::Environment),
+            "version" => Ok(Self::Version),
+            "os" => Ok(Self::Os),
+            "title" => Ok(Self::Title),
+            "log" => Ok(Self::Log),
+            _ => Err(anyhow::anyhow!("Unknown filter field: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// Case insensitive comparison
+    Exact(String),
+    /// Semantic version requirement, e.g. "<1.2.0"
+    VersionReq(semver::VersionReq),
+    Pattern(Regex),
+}
+
+/// A project inbound filter, events matching any of the filters are dropped before they are counted
+#[derive(Debug, Clone)]
+pub struct InboundFilter {
+    pub field: FilterField,
+    matcher: Matcher,
+}
+
+impl InboundFilter {
+    pub fn new(field: &str, pattern: &str) -> anyhow::Result<Self> {
+        let field = field.parse()?;
+
+        let matcher = match field {
+            FilterField::Environment | FilterField::Os => Matcher::Exact(pattern.trim().to_lowercase()),
+            FilterField::Version => Matcher::VersionReq(
+                pattern
+                    .parse()
+                    .map_err(|e| anyhow::anyhow!("Invalid version range: {}", e))?,
+            ),
+            FilterField::Title | FilterField::Log => Matcher::Pattern(compile_pattern(pattern)?),
+        };
+
+        Ok(Self { field, matcher })
+    }
+
+    fn matches(&self, event: &FilteredEvent) -> bool {
+        let is_match = |text: &str| match &self.matcher {
+            Matcher::Exact(value) => text.to_lowercase() == *value,
+            Matcher::VersionReq(req) => releases::parse(text).is_some_and(|version| req.matches(&version)),
+            Matcher::Pattern(regex) => regex.is_match(text),
+        };
+
+        match self.field {
+            FilterField::Environment => event.environment.is_some_and(is_match),
+            FilterField::Version => event.version.is_some_and(is_match),
+            FilterField::Os => is_match(event.os),
+            FilterField::Title => is_match(event.title),
+            FilterField::Log => event.log_messages.iter().any(|message| is_match(message)),
+        }
+    }
+}
+
+impl TryFrom<&project_inbound_filters::Model> for InboundFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(filter: &project_inbound_filters::Model) -> Result<Self, Self::Error> {
+        Self::new(&filter.field, &filter.pattern)
+    }
+}
+
+/// Loads the inbound filters of a project
+pub async fn project_filters(db: &DatabaseConnection, project_id: u32) -> anyhow::Result<Vec<InboundFilter>> {
+    let rows = ProjectInboundFilters::find()
+        .filter(project_inbound_filters::Column::ProjectId.eq(project_id))
+        .all(db)
+        .await?;
+
+    // a broken filter is logged and skipped, the event is still checked against the others
+    let filters = rows
+        .iter()
+        .filter_map(|row| match InboundFilter::try_from(row) {
+            Ok(filter) => Some(filter),
+            Err(e) => {
+                log::warn!("Skipping inbound filter {}: {}", row.project_inbound_filter_id, e);
+                None
+            }
+        })
+        .collect();
+
+    Ok(filters)
+}
+
+/// The parts of an incoming event filters can match against
+pub struct FilteredEvent<'a> {
+    pub environment: Option<&'a str>,
+    pub version: Option<&'a str>,
+    pub os: &'a str,
+    pub title: &'a str,
+    pub log_messages: Vec<&'a str>,
+}
+
+impl FilteredEvent<'_> {
+    /// The first filter that drops this event
+    pub fn dropped_by<'f>(&self, filters: &'f [InboundFilter]) -> Option<&'f InboundFilter> {
+        filters.iter().find(|filter| filter.matches(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters() {
+        let event = FilteredEvent {
+            environment: Some("Staging"),
+            version: Some("v1.1.0"),
+            os: "linux",
+            title: "Connection refused",
+            log_messages: vec!["retrying request"],
+        };
+
+        let dropped = |field, pattern| {
+            event
+                .dropped_by(&[InboundFilter::new(field, pattern).unwrap()])
+                .is_some()
+        };
+
+        assert!(dropped("environment", "staging"));
+        assert!(!dropped("environment", "production"));
+        assert!(dropped("version", "<1.2.0"));
+        assert!(!dropped("version", ">=1.2.0"));
+        assert!(dropped("os", "Linux"));
+        assert!(dropped("title", "^Connection"));
+        assert!(dropped("log", "retry"));
+        assert!(!dropped("log", "^request"));
+
+        assert!(InboundFilter::new("version", "not a range").is_err());
+        assert!(InboundFilter::new("arch", "x86_64").is_err());
+    }
+}
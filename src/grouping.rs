@@ -0,0 +1,316 @@
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::str::FromStr;
+
+use regex::{Regex, RegexBuilder};
+use sea_orm::{prelude::*, QueryOrder};
+use sha2::{Digest, Sha256};
+
+use crate::backtrace::Frame;
+use crate::entity::prelude::*;
+use crate::entity::project_grouping_rules;
+use crate::handlers::ingress::normalize_title;
+
+// number of application frames from the top of the backtrace used by the frames strategy
+const FINGERPRINT_FRAMES: usize = 3;
+// client supplied fingerprints are clamped to these limits
+const MAX_CUSTOM_PARTS: usize = 16;
+const MAX_CUSTOM_PART_LEN: usize = 256;
+// placeholder in a client fingerprint that is replaced with the project's grouping key
+const DEFAULT_PLACEHOLDER: &str = "{{ default }}";
+// keeps user supplied patterns from using excessive memory on every ingested event
+const MAX_PATTERN_SIZE: usize = 256 * 1024;
+
+/// How events are grouped into reports, configurable per project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupingStrategy {
+    /// Normalized event title, which includes the panic location
+    #[default]
+    Title,
+    /// Panic location and the top application frames of the backtrace
+    Frames,
+}
+
+impl GroupingStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Frames => "frames",
+        }
+    }
+}
+
+impl fmt::Display for GroupingStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for GroupingStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(Self::Title),
+            "frames" => Ok(Self::Frames),
+            _ => Err(anyhow::anyhow!("Unknown grouping strategy: {}", s)),
+        }
+    }
+}
+
+/// Part of an event a grouping rule is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleField {
+    Title,
+    /// Module path of a frame, the symbol without the function name
+    Module,
+    Symbol,
+}
+
+impl FromStr for RuleField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(Self::Title),
+            "module" => Ok(Self::Module),
+            "symbol" => Ok(Self::Symbol),
+            _ => Err(anyhow::anyhow!("Unknown rule field: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RuleAction {
+    /// Rewrites the matched text before the grouping key is computed
+    Replace(String),
+    /// Puts every matching event into the named group, regardless of the grouping strategy
+    Group(String),
+}
+
+/// A project grouping rule with its pattern compiled
+#[derive(Debug, Clone)]
+pub struct GroupingRule {
+    pub field: RuleField,
+    pub pattern: Regex,
+    pub action: RuleAction,
+}
+
+/// Compiles a pattern of a grouping rule or inbound filter
+pub fn compile_pattern(pattern: &str) -> anyhow::Result<Regex> {
+    RegexBuilder::new(pattern)
+        .size_limit(MAX_PATTERN_SIZE)
+        .build()
+        .map_err(|e| anyhow::anyhow!("Invalid pattern: {}", e))
+}
+
+impl GroupingRule {
+    pub fn new(field: &str, action: &str, pattern: &str, replacement: Option<&str>) -> anyhow::Result<Self> {
+        let field = field.parse()?;
+
+        let pattern = compile_pattern(pattern)?;
+
+        let action = match action {
+            "replace" => RuleAction::Replace(replacement.unwrap_or_default().to_string()),
+            // unnamed groups are identified by their pattern
+            "group" => RuleAction::Group(
+                replacement
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or(pattern.as_str())
+                    .to_string(),
+            ),
+            _ => anyhow::bail!("Unknown rule action: {}", action),
+        };
+
+        Ok(Self { field, pattern, action })
+    }
+
+    fn replace(&self, field: RuleField, text: &str) -> Option<String> {
+        match &self.action {
+            RuleAction::Replace(replacement) if self.field == field => {
+                Some(self.pattern.replace_all(text, replacement.as_str()).into_owned())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<&project_grouping_rules::Model> for GroupingRule {
+    type Error = anyhow::Error;
+
+    fn try_from(rule: &project_grouping_rules::Model) -> Result<Self, Self::Error> {
+        Self::new(&rule.field, &rule.action, &rule.pattern, rule.replacement.as_deref())
+    }
+}
+
+/// Loads the grouping rules of a project in the order they are applied
+pub async fn project_rules(db: &impl ConnectionTrait, project_id: u32) -> anyhow::Result<Vec<GroupingRule>> {
+    let rows = ProjectGroupingRules::find()
+        .filter(project_grouping_rules::Column::ProjectId.eq(project_id))
+        .order_by_asc(project_grouping_rules::Column::Position)
+        .all(db)
+        .await?;
+
+    // rules are validated when saved, a rule that no longer compiles shouldn't stop ingestion
+    let rules = rows
+        .iter()
+        .filter_map(|row| match GroupingRule::try_from(row) {
+            Ok(rule) => Some(rule),
+            Err(e) => {
+                log::warn!("Skipping grouping rule {}: {}", row.project_grouping_rule_id, e);
+                None
+            }
+        })
+        .collect();
+
+    Ok(rules)
+}
+
+pub fn environment_hash(name: &str) -> u64 {
+    let mut s = DefaultHasher::new();
+    name.hash(&mut s);
+    s.finish()
+}
+
+pub struct EventFingerprint<'a> {
+    pub project_id: u32,
+    pub environment_hash: u64,
+    pub title: &'a str,
+    pub location: Option<&'a str>,
+    pub frames: &'a [Frame],
+    pub custom: Option<&'a [String]>,
+    pub rules: &'a [GroupingRule],
+}
+
+impl EventFingerprint<'_> {
+    /// Reports with the same uid are considered the same issue.
+    /// The frames strategy falls back to the title when the backtrace has no application frames.
+    /// A fingerprint sent by the client replaces the strategy completely,
+    /// only the project's group rules take precedence over it.
+    pub fn report_uid(&self, strategy: GroupingStrategy) -> String {
+        let grouping_key = match self.group_key() {
+            Some(key) => key,
+            None => {
+                // replace rules are applied to the inputs of the default grouping
+                let title = self.rewrite(RuleField::Title, self.title);
+                let frames: Vec<Frame> = self
+                    .frames
+                    .iter()
+                    .map(|frame| Frame {
+                        symbol: self.rewrite_symbol(&frame.symbol),
+                        ..frame.clone()
+                    })
+                    .collect();
+
+                let rewritten = EventFingerprint {
+                    title: &title,
+                    frames: &frames,
+                    rules: &[],
+                    ..*self
+                };
+
+                match self.custom.filter(|parts| !parts.is_empty()) {
+                    Some(parts) => rewritten.custom_key(parts, strategy),
+                    None => rewritten.default_key(strategy),
+                }
+            }
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(format!(
+            "p{}-{}-{}",
+            self.project_id, self.environment_hash, grouping_key
+        ));
+        format!("{:X}", hasher.finalize())
+    }
+
+    fn group_key(&self) -> Option<String> {
+        self.rules.iter().find_map(|rule| {
+            let RuleAction::Group(name) = &rule.action else {
+                return None;
+            };
+
+            let matches = match rule.field {
+                RuleField::Title => rule.pattern.is_match(self.title),
+                RuleField::Module => self
+                    .frames
+                    .iter()
+                    .any(|f| rule.pattern.is_match(module_path(&f.symbol).0)),
+                RuleField::Symbol => self.frames.iter().any(|f| rule.pattern.is_match(&f.symbol)),
+            };
+
+            matches.then(|| format!("group:{}", name))
+        })
+    }
+
+    fn rewrite(&self, field: RuleField, text: &str) -> String {
+        self.rules.iter().fold(text.to_string(), |text, rule| {
+            rule.replace(field, &text).unwrap_or(text)
+        })
+    }
+
+    fn rewrite_symbol(&self, symbol: &str) -> String {
+        let (module, function) = module_path(symbol);
+        let module = self.rewrite(RuleField::Module, module);
+
+        let symbol = match function {
+            Some(function) => format!("{}::{}", module, function),
+            None => module,
+        };
+
+        self.rewrite(RuleField::Symbol, &symbol)
+    }
+
+    fn default_key(&self, strategy: GroupingStrategy) -> String {
+        let key = match strategy {
+            GroupingStrategy::Title => None,
+            GroupingStrategy::Frames => self.frames_key(),
+        };
+
+        key.unwrap_or_else(|| normalize_title(self.title))
+    }
+
+    fn custom_key(&self, parts: &[String], strategy: GroupingStrategy) -> String {
+        let parts: Vec<String> = parts
+            .iter()
+            .take(MAX_CUSTOM_PARTS)
+            .map(|part| {
+                if part.trim() == DEFAULT_PLACEHOLDER {
+                    self.default_key(strategy)
+                } else {
+                    part.chars().take(MAX_CUSTOM_PART_LEN).collect()
+                }
+            })
+            .collect();
+
+        format!("custom:{}", serde_json::to_string(&parts).unwrap_or_default())
+    }
+
+    fn frames_key(&self) -> Option<String> {
+        let symbols: Vec<&str> = self
+            .frames
+            .iter()
+            .filter(|f| f.in_app)
+            .take(FINGERPRINT_FRAMES)
+            .map(|f| f.symbol.as_str())
+            .collect();
+
+        if symbols.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "frames:{}:{}",
+            self.location.unwrap_or_default(),
+            symbols.join("|")
+        ))
+    }
+}
+
+/// Splits a symbol into its module path and function name, generic arguments are kept with the module
+fn module_path(symbol: &str) -> (&str, Option<&str>) {
+    match symbol.rsplit_once("::") {
+        Some((module, function)) if !function.contains('>') => (module, Some(function)),
+        _ => (symbol, None),
+    }
+}
@@ -1,27 +1,36 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
 
-use actix_web::{post, web, HttpResponse};
+use actix_web::{error::JsonPayloadError, post, web, HttpRequest, HttpResponse};
 use chrono::prelude::*;
 use lettre::AsyncTransport;
 use regex::Regex;
 use sea_orm::prelude::*;
 use sea_orm::sea_query;
-use sea_orm::{ActiveValue, IntoActiveModel, JoinType, QueryOrder, QuerySelect, TryIntoModel};
+use sea_orm::{ActiveValue, Condition, IntoActiveModel, JoinType, QuerySelect, TransactionTrait, TryIntoModel};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use tokio::sync::OwnedMutexGuard;
 
 use crate::entity::organizations;
 use crate::entity::prelude::*;
 use crate::entity::project_environments;
+use crate::entity::project_keys;
 use crate::entity::project_report_events;
+use crate::entity::project_report_merges;
 use crate::entity::project_report_stats;
 use crate::entity::project_reports;
 use crate::entity::projects;
+use crate::entity::queued_events;
 use crate::entity::{organization_stats, organization_users};
 
+use crate::backtrace::Frame;
+use crate::config::Config;
 use crate::entity::users;
-use crate::notifications::{Notification, ReportStatus};
+use crate::filters::{self, FilteredEvent};
+use crate::grouping::{self, EventFingerprint, GroupingRule, GroupingStrategy};
+use crate::notifications::{outbox, Notification, ReportStatus};
+use crate::rate_limit::{Limited, Limits};
+use crate::releases;
 use crate::{AppContext, Error, Result};
 
 // To preserve backwards compatibility with any client version,
@@ -70,34 +79,206 @@ struct EventData {
     backtrace: String,
     #[serde(rename = "log")]
     log_messages: Vec<LogEvent>,
+    // overrides the report grouping, "{{ default }}" entries are replaced with the project grouping key
+    fingerprint: Option<Vec<String>>,
+    // searchable key/value pairs, counted per report like os and version
+    tags: Option<BTreeMap<String, String>>,
+    // free-form data stored with the event only
+    context: Option<serde_json::Value>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 struct Event {
     key: String,
     env: Option<String>,
     data: EventData,
 }
 
+impl EventData {
+    /// Title stored with the report, includes the event location and is limited to 500 characters
+    fn report_title(&self) -> String {
+        let event_location = self
+            .location
+            .as_ref()
+            .map(|l| format!("{}:{}", l.file, l.line))
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let event_location_len = event_location.chars().count();
+
+        let title_upto = self
+            .title
+            .char_indices()
+            .enumerate()
+            .map(|(char_idx, (byte_idx, _))| (char_idx, byte_idx))
+            .find(|(i, _)| *i >= (490 - event_location_len - 3));
+
+        let truncated_title = if let Some((_, byte_idx)) = title_upto {
+            let mut title = self.title.clone();
+            title.truncate(byte_idx);
+            title.push_str("...");
+            title
+        } else {
+            self.title.clone()
+        };
+
+        format!("{} in {}", truncated_title, event_location)
+    }
+}
+
+/// Payload of the ingress queue, the report uid and frames are computed once when the event is accepted
+#[derive(Serialize, Deserialize, Debug)]
+struct QueuedEvent {
+    event: Event,
+    uid: String,
+    frames: Vec<Frame>,
+}
+
+/// Tells the client what happened to an event, so it can throttle itself
+#[derive(Serialize, Debug)]
+struct IngressResult {
+    /// Report the event is grouped into, missing when the organization is disabled
+    uid: Option<String>,
+    /// Whether the event is queued to be stored, filtered and rate limited events are only counted
+    stored: bool,
+    /// Suggested share of events the client should send, between 0 and 1
+    sample_rate: f64,
+    /// Seconds the client should wait before sending more events
+    backoff: u32,
+}
+
+#[derive(Serialize, Debug)]
+struct BatchItemResult {
+    accepted: bool,
+    error: Option<String>,
+    #[serde(flatten)]
+    result: Option<IngressResult>,
+}
+
+// upper bound of events accepted in a single batch request
+const MAX_BATCH_SIZE: usize = 100;
+// tags past these limits are dropped or truncated
+const MAX_TAGS: usize = 50;
+const MAX_TAG_KEY_LEN: usize = 64;
+const MAX_TAG_VALUE_LEN: usize = 200;
+// serialized context larger than this is not stored
+const MAX_CONTEXT_SIZE: usize = 16 * 1024;
+// seconds clients of a disabled organization are asked to wait
+const DISABLED_BACKOFF: u32 = 60 * 60;
+
 pub fn routes(cfg: &mut web::ServiceConfig) {
-    cfg.service(ingress);
+    cfg.service(ingress).service(ingress_batch);
+}
+
+/// Gzip and zstd request bodies are decompressed by actix before parsing,
+/// so the size limit applies to the decompressed payload
+pub fn json_config(config: &Config) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(config.ingress_max_payload_size)
+        .error_handler(|err, _req| match err {
+            JsonPayloadError::Overflow { limit } | JsonPayloadError::OverflowKnownLength { limit, .. } => {
+                Error::PayloadTooLarge(limit).into()
+            }
+            e => Error::new(e.to_string()).into(),
+        })
+}
+
+#[post("")]
+async fn ingress(
+    ctx: web::Data<AppContext<'static>>,
+    req: HttpRequest,
+    event: web::Json<Event>,
+) -> Result<HttpResponse> {
+    let client_ip_hash = client_ip_hash(&ctx, &req);
+
+    let result = accept(&ctx, event.into_inner(), client_ip_hash).await?;
+
+    Ok(HttpResponse::Ok().json(result))
 }
 
-#[post("/ingress")]
-async fn ingress(ctx: web::Data<AppContext<'static>>, event: web::Json<Event>) -> Result<HttpResponse> {
-    let event = event.into_inner();
+#[post("/batch")]
+async fn ingress_batch(
+    ctx: web::Data<AppContext<'static>>,
+    req: HttpRequest,
+    events: web::Json<Vec<serde_json::Value>>,
+) -> Result<HttpResponse> {
+    let events = events.into_inner();
+    let client_ip_hash = client_ip_hash(&ctx, &req);
+
+    if events.len() > MAX_BATCH_SIZE {
+        return Err(Error::new(format!(
+            "A batch can contain at most {} events",
+            MAX_BATCH_SIZE
+        )));
+    }
+
+    let mut results = Vec::with_capacity(events.len());
+
+    // items are validated and queued one by one in the order they were sent,
+    // so a malformed event doesn't reject the rest of the batch
+    for value in events {
+        let res = match serde_json::from_value::<Event>(value) {
+            Ok(event) => accept(&ctx, event, client_ip_hash.clone()).await,
+            Err(e) => Err(Error::new(format!("Invalid event: {}", e))),
+        };
+
+        let item = match res {
+            Ok(result) => BatchItemResult {
+                accepted: true,
+                error: None,
+                result: Some(result),
+            },
+            Err(e) => {
+                // earlier items are already queued, so internal errors are reported per item too
+                if let Error::Internal(e) = &e {
+                    log::error!("{:?}", e);
+                }
+
+                BatchItemResult {
+                    accepted: false,
+                    error: Some(e.to_string()),
+                    result: None,
+                }
+            }
+        };
+
+        results.push(item);
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "results": results })))
+}
 
-    let maybe_project = Projects::find()
-        .filter(projects::Column::ApiKey.eq(&event.key))
+/// Checks the api key, organization status, inbound filters and rate limits,
+/// then persists the event for processing by the ingress queue workers
+async fn accept(ctx: &AppContext<'static>, mut event: Event, client_ip_hash: Option<String>) -> Result<IngressResult> {
+    let now = Utc::now().naive_utc();
+
+    let maybe_key = ProjectKeys::find()
+        .filter(project_keys::Column::Key.eq(&event.key))
+        .filter(project_keys::Column::Revoked.is_null())
+        .filter(
+            Condition::any()
+                .add(project_keys::Column::Expires.is_null())
+                .add(project_keys::Column::Expires.gt(now)),
+        )
+        .find_also_related(Projects)
         .one(&ctx.db)
         .await?;
 
-    let Some(project) = maybe_project else {
+    let Some((key, Some(project))) = maybe_key else {
         return Err(Error::new("API key not found or organization disabled"));
     };
 
-    // make sure to handle events sequentially per project
-    let lock = ctx.locked_projects.lock(project.project_id).await;
+    // last use is only tracked to the minute, to avoid a write for every event
+    if key
+        .last_used
+        .is_none_or(|last_used| now - last_used >= chrono::Duration::minutes(1))
+    {
+        ProjectKeys::update_many()
+            .col_expr(project_keys::Column::LastUsed, Expr::value(now))
+            .filter(project_keys::Column::ProjectKeyId.eq(key.project_key_id))
+            .exec(&ctx.db)
+            .await?;
+    }
 
     // limits check
     let org = project
@@ -107,62 +288,315 @@ async fn ingress(ctx: web::Data<AppContext<'static>>, event: web::Json<Event>) -
         .expect("Each project must have organization");
 
     if org.is_enabled == 0 {
-        return Err(Error::new("Organization requests limit exceeded"));
+        return Ok(IngressResult {
+            uid: None,
+            stored: false,
+            sample_rate: 0.0,
+            backoff: DISABLED_BACKOFF,
+        });
     }
 
-    // Handle everything in bg to not hold client waiting
-    actix_web::rt::spawn(async move {
-        if let Err(e) = ingress_background(ctx, event, org, project, lock).await {
-            log::error!("Error handling incoming event: {:?}", e);
-        }
-    });
+    event.env = resolve_environment(ctx, &project, &key, event.env).await?;
+
+    let environment_key = match &event.env {
+        Some(name) => find_environment(&ctx.db, project.project_id, name)
+            .await?
+            .map(|environment| environment.grouping_name)
+            .unwrap_or_else(|| name.clone()),
+        None => String::new(),
+    };
+
+    let event_title = event.data.report_title();
+    // parse frames from the full backtrace, before it gets truncated
+    let frames = crate::backtrace::parse(&event.data.backtrace);
+    let rules = grouping::project_rules(&ctx.db, project.project_id).await?;
+    let uid = report_uid(&project, &rules, &event, &environment_key, &event_title, &frames);
+
+    // filtered events don't count against the organization limits
+    let filters = filters::project_filters(&ctx.db, project.project_id).await?;
+
+    let filtered = FilteredEvent {
+        environment: event.env.as_deref(),
+        version: event.data.version.as_deref(),
+        os: &event.data.os,
+        title: &event.data.title,
+        log_messages: event.data.log_messages.iter().map(|log| log.message.as_str()).collect(),
+    };
+
+    if let Some(filter) = filtered.dropped_by(&filters) {
+        record_org_stat(&ctx.db, org.organization_id, "dropped", filter.field.as_str()).await?;
+
+        return Ok(IngressResult {
+            uid: Some(uid),
+            stored: false,
+            sample_rate: 1.0,
+            backoff: 0,
+        });
+    }
+
+    // events over the rate limits are only counted, like filtered ones
+    let limits = Limits {
+        project: project.rate_limit,
+        report: project.report_rate_limit,
+        spike_multiplier: Some(ctx.config.spike_protection_multiplier)
+            .filter(|multiplier| *multiplier > 0 && project.spike_protection > 0),
+    };
+
+    let checked = ctx.rate_limiter.check(project.project_id, &uid, &limits);
+    let sample_rate = ctx.rate_limiter.sample_rate(project.project_id, &limits);
+
+    if let Err(limited) = checked {
+        record_org_stat(&ctx.db, org.organization_id, "rate_limited", limited.as_str()).await?;
 
-    Ok(HttpResponse::Ok().finish())
+        // only the project wide limits ask the client to stop sending altogether
+        let backoff = match limited {
+            Limited::Report => 0,
+            Limited::Project | Limited::Spike => 60 - Utc::now().second(),
+        };
+
+        return Ok(IngressResult {
+            uid: Some(uid),
+            stored: false,
+            sample_rate,
+            backoff,
+        });
+    }
+
+    let queued = QueuedEvent {
+        event,
+        uid: uid.clone(),
+        frames,
+    };
+
+    crate::queue::push(
+        ctx,
+        project.project_id,
+        serde_json::to_string(&queued)?,
+        client_ip_hash,
+        key.project_key_id,
+    )
+    .await?;
+
+    Ok(IngressResult {
+        uid: Some(uid),
+        stored: true,
+        sample_rate,
+        backoff: 0,
+    })
 }
 
-async fn ingress_background(
-    ctx: web::Data<AppContext<'static>>,
-    event: Event,
-    org: organizations::Model,
-    project: projects::Model,
-    _lock: OwnedMutexGuard<()>,
-) -> Result<()> {
-    if let Some(request_limit) = org.requests_limit {
-        let request_count = org.requests_count.unwrap_or_default();
+/// Applies the environment restrictions of the key and project to the environment an event was sent to
+async fn resolve_environment(
+    ctx: &AppContext<'_>,
+    project: &projects::Model,
+    key: &project_keys::Model,
+    env: Option<String>,
+) -> Result<Option<String>> {
+    let find_environment = |project_environment_id: u32| {
+        ProjectEnvironments::find_by_id(project_environment_id)
+            .filter(project_environments::Column::ProjectId.eq(project.project_id))
+            .one(&ctx.db)
+    };
+
+    if let Some(project_environment_id) = key.project_environment_id {
+        let Some(bound) = find_environment(project_environment_id).await? else {
+            return Err(Error::new("The environment of this API key no longer exists"));
+        };
+
+        return match env {
+            Some(name) if name != bound.name && Some(&name) != bound.grouping_name.as_ref() => Err(Error::new(
+                format!("This API key can only send events to the {} environment", bound.name),
+            )),
+            _ => Ok(Some(bound.name)),
+        };
+    }
+
+    let Some(name) = env else {
+        return Ok(None);
+    };
+
+    if project.auto_create_environments > 0 {
+        return Ok(Some(name));
+    }
+
+    let exists = ProjectEnvironments::find()
+        .filter(project_environments::Column::ProjectId.eq(project.project_id))
+        .filter(
+            Condition::any()
+                .add(project_environments::Column::Name.eq(&name))
+                .add(project_environments::Column::GroupingName.eq(&name)),
+        )
+        .one(&ctx.db)
+        .await?
+        .is_some();
+
+    if exists {
+        return Ok(Some(name));
+    }
+
+    let default = match project.default_environment_id {
+        Some(project_environment_id) => find_environment(project_environment_id).await?,
+        None => None,
+    };
+
+    match default {
+        Some(default) => Ok(Some(default.name)),
+        None => Err(Error::new(format!("Unknown environment {}", name))),
+    }
+}
+
+/// Environment an event is stored in, with the name its reports are grouped by
+struct EventEnvironment {
+    environment: project_environments::Model,
+    grouping_name: String,
+}
+
+/// Finds the environment events sent with `name` belong to. Renamed environments keep grouping
+/// by their original name and events sent to a merged environment are assigned to the merge target.
+async fn find_environment(db: &impl ConnectionTrait, project_id: u32, name: &str) -> Result<Option<EventEnvironment>> {
+    // renamed environments are still found by the name their reports are grouped with
+    let Some(found) = ProjectEnvironments::find()
+        .filter(
+            Condition::any()
+                .add(project_environments::Column::Name.eq(name))
+                .add(project_environments::Column::GroupingName.eq(name)),
+        )
+        .filter(project_environments::Column::ProjectId.eq(project_id))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let grouping_name = found.grouping_name.clone().unwrap_or_else(|| found.name.clone());
+
+    let environment = match found.merged_into_id {
+        Some(merged_into_id) => ProjectEnvironments::find_by_id(merged_into_id)
+            .filter(project_environments::Column::ProjectId.eq(project_id))
+            .one(db)
+            .await?
+            .unwrap_or(found),
+        None => found,
+    };
+
+    Ok(Some(EventEnvironment {
+        environment,
+        grouping_name,
+    }))
+}
+
+/// Computes the uid of the report an event belongs to with the project's grouping settings
+fn report_uid(
+    project: &projects::Model,
+    rules: &[GroupingRule],
+    event: &Event,
+    environment_key: &str,
+    event_title: &str,
+    frames: &[Frame],
+) -> String {
+    let location = event.data.location.as_ref().map(|l| format!("{}:{}", l.file, l.line));
+    let strategy = project.grouping.parse::<GroupingStrategy>().unwrap_or_default();
+
+    EventFingerprint {
+        project_id: project.project_id,
+        environment_hash: grouping::environment_hash(environment_key),
+        title: event_title,
+        location: location.as_deref(),
+        frames,
+        custom: event.data.fingerprint.as_deref(),
+        rules,
+    }
+    .report_uid(strategy)
+}
+
+/// Identifies events sent from the same address without storing the address itself
+fn client_ip_hash(ctx: &AppContext<'_>, req: &HttpRequest) -> Option<String> {
+    let conn = req.connection_info();
+    let addr = conn.realip_remote_addr()?;
+
+    // the peer address includes the port, forwarded headers usually don't
+    let ip = addr
+        .parse::<SocketAddr>()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|_| addr.to_string());
+
+    let mut hasher = Sha256::new();
+    hasher.update(ctx.config.cookie_secret);
+    hasher.update(ip.as_bytes());
+
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Handles an event from the ingress queue. The caller is expected to hold the project lock.
+///
+/// The event is stored and removed from the queue in a single transaction,
+/// so retrying a failed event never counts it twice.
+pub(crate) async fn process_queued(ctx: &AppContext<'static>, row: &queued_events::Model) -> Result<()> {
+    let queued: QueuedEvent = serde_json::from_str(&row.payload)?;
+
+    let txn = ctx.db.begin().await?;
+
+    let project = Projects::find_by_id(row.project_id)
+        .one(&txn)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let org = project
+        .find_related(Organizations)
+        .one(&txn)
+        .await?
+        .expect("Each project must have organization");
 
-        // send an email when 90% of the limit is reached
-        if request_limit * 9 / 10 == request_count {
+    let notification = ingress_background(&txn, queued, &org, project, row).await?;
+
+    if let Some(notification) = &notification {
+        outbox::enqueue(&txn, notification).await?;
+    }
+
+    QueuedEvents::delete_by_id(row.queued_event_id).exec(&txn).await?;
+
+    txn.commit().await?;
+
+    // send an email when 90% of the limit is reached
+    if let Some(request_limit) = org.requests_limit {
+        if request_limit * 9 / 10 == org.requests_count.unwrap_or_default() {
             let bg_ctx = ctx.clone();
-            let bg_org = org.clone();
 
             actix_web::rt::spawn(async move {
-                if let Err(e) = notify_limit_approaching(&bg_ctx, &bg_org).await {
+                if let Err(e) = notify_limit_approaching(&bg_ctx, &org).await {
                     log::error!("Error sending limit reached notification: {:?}", e);
                 }
             });
         }
-
-        if request_count >= request_limit {
-            return Err(Error::new("Organization requests limit exceeded"));
-        } else {
-            let mut row = org.clone().into_active_model();
-            row.requests_count = ActiveValue::set(Some(request_count + 1));
-            row.save(&ctx.db).await?;
-        }
     }
 
-    record_org_stat(&ctx.db, org.organization_id, "event", "total_count").await?;
+    let Some(notification) = notification else {
+        return Ok(());
+    };
 
-    // find environment or create it
-    let environment = if let Some(env_ident) = event.env {
-        let maybe_env = ProjectEnvironments::find()
-            .filter(project_environments::Column::Name.eq(&env_ident))
-            .filter(project_environments::Column::ProjectId.eq(project.project_id))
-            .one(&ctx.db)
-            .await?;
+    ctx.notification_queue.notify_waiters();
+
+    // there might be no clients listening for live updates
+    let _ = ctx.notifications.send(notification);
 
-        let environment = match maybe_env {
-            Some(env_row) => env_row,
+    Ok(())
+}
+
+/// Stores a queued event, returns the notification to send about it
+async fn ingress_background(
+    db: &impl ConnectionTrait,
+    queued: QueuedEvent,
+    org: &organizations::Model,
+    project: projects::Model,
+    row: &queued_events::Model,
+) -> Result<Option<Notification>> {
+    let QueuedEvent { event, uid, frames } = queued;
+    let event_title = event.data.report_title();
+
+    // find environment or create it
+    let environment = if let Some(env_ident) = &event.env {
+        let environment = match find_environment(db, project.project_id, env_ident).await? {
+            Some(environment) => environment.environment,
             None => {
                 let env_row = project_environments::ActiveModel {
                     project_id: ActiveValue::set(project.project_id),
@@ -170,7 +604,7 @@ async fn ingress_background(
                     ..Default::default()
                 };
 
-                env_row.save(&ctx.db).await?.try_into_model()?
+                env_row.save(db).await?.try_into_model()?
             }
         };
 
@@ -179,81 +613,104 @@ async fn ingress_background(
         None
     };
 
-    let environment_hash = {
-        let mut s = DefaultHasher::new();
-        environment
-            .as_ref()
-            .map(|e| e.name.as_str())
-            .unwrap_or_default()
-            .hash(&mut s);
-        s.finish()
-    };
+    if let Some(request_limit) = org.requests_limit {
+        let request_count = org.requests_count.unwrap_or_default();
 
-    // Enforce event title limit to 500 characters, including the event location
-    let event_location = event
-        .data
-        .location
-        .as_ref()
-        .map(|l| format!("{}:{}", l.file, l.line))
-        .unwrap_or_else(|| "Unknown".to_string());
-
-    let event_location_len = event_location.chars().count();
-
-    let title_upto = event
-        .data
-        .title
-        .char_indices()
-        .enumerate()
-        .map(|(char_idx, (byte_idx, _))| (char_idx, byte_idx))
-        .find(|(i, _)| *i >= (490 - event_location_len - 3));
-
-    let truncated_title = if let Some((_, byte_idx)) = title_upto {
-        let mut title = event.data.title.clone();
-        title.truncate(byte_idx);
-        title.push_str("...");
-        title
-    } else {
-        event.data.title
-    };
+        // events over the limit are only counted, retrying them won't help
+        if request_count >= request_limit {
+            record_org_stat(db, org.organization_id, "dropped", "requests_limit").await?;
+            return Ok(None);
+        }
 
-    let event_title = format!("{} in {}", truncated_title, event_location);
+        let mut row = org.clone().into_active_model();
+        row.requests_count = ActiveValue::set(Some(request_count + 1));
+        row.save(db).await?;
+    }
 
-    let mut hasher = Sha256::new();
-    hasher.update(format!(
-        "p{}-{}-{}",
-        project.project_id,
-        environment_hash,
-        normalize_title(&event_title)
-    ));
-    let uid = format!("{:X}", hasher.finalize());
+    record_org_stat(db, org.organization_id, "event", "total_count").await?;
 
     // find relevant report or create it
-    let maybe_report = ProjectReports::find()
+    let mut maybe_report = ProjectReports::find()
         .filter(project_reports::Column::Uid.eq(&uid))
-        .one(&ctx.db)
+        .one(db)
         .await?;
 
+    // events of a report merged into another one keep landing in the merge target
+    let mut merge_id = None;
+
+    if maybe_report.is_none() {
+        let maybe_merge = ProjectReportMerges::find()
+            .filter(project_report_merges::Column::Uid.eq(&uid))
+            .one(db)
+            .await?;
+
+        if let Some(merge) = maybe_merge {
+            merge_id = Some(merge.project_report_merge_id);
+            maybe_report = merge.find_related(ProjectReports).one(db).await?;
+        }
+    }
+
+    let version = event.data.version.as_deref().map(|v| truncate(v, 64));
+
+    if let Some(version) = version.as_deref() {
+        releases::record(db, project.project_id, version).await?;
+    }
+
     let mut report_status: Option<ReportStatus> = None;
+    let mut still_ignored = false;
 
     let report_model = match maybe_report {
         Some(report) => {
-            if report.is_resolved > 0 {
+            still_ignored = is_still_ignored(db, &report, version.as_deref()).await?;
+
+            // reports resolved in a release only regress when they appear in a newer one
+            let still_resolved = match (report.resolved_in_version.as_deref(), version.as_deref()) {
+                _ if report.is_resolved == 0 => false,
+                (Some(resolved_in), Some(version)) => {
+                    !releases::is_newer(db, project.project_id, version, resolved_in).await?
+                }
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+
+            if report.is_resolved > 0 && !still_resolved {
                 // issue marked as resolved, but reappears again
                 report_status = Some(ReportStatus::Regressed);
             }
 
             let mut report_model = report.into_active_model();
             report_model.last_seen = ActiveValue::set(Utc::now().naive_utc());
-            report_model.is_resolved = ActiveValue::set(0);
-            report_model.is_seen = ActiveValue::set(0);
             report_model.title = ActiveValue::set(event_title);
+
+            if !still_resolved {
+                report_model.is_resolved = ActiveValue::set(0);
+                report_model.is_seen = ActiveValue::set(0);
+                report_model.resolved_in_version = ActiveValue::set(None);
+            }
+
+            if version.is_some() {
+                report_model.last_version = ActiveValue::set(version.clone());
+            }
+
+            if still_ignored {
+                if let ActiveValue::Unchanged(Some(left)) = report_model.ignored_events_left {
+                    report_model.ignored_events_left = ActiveValue::set(Some(left.saturating_sub(1)));
+                }
+            } else {
+                report_model.is_ignored = ActiveValue::set(0);
+                report_model.ignore_condition = ActiveValue::set(None);
+                report_model.ignored_until = ActiveValue::set(None);
+                report_model.ignored_events_left = ActiveValue::set(None);
+                report_model.ignored_in_version = ActiveValue::set(None);
+            }
+
             report_model
         }
         None => {
             report_status = Some(ReportStatus::New);
 
             record_org_stat(
-                &ctx.db,
+                db,
                 org.organization_id,
                 "new_project_report",
                 &project.project_id.to_string(),
@@ -266,12 +723,14 @@ async fn ingress_background(
                 uid: ActiveValue::set(uid),
                 title: ActiveValue::set(event_title),
                 project_environment_id: ActiveValue::set(environment.as_ref().map(|e| e.project_environment_id)),
+                first_version: ActiveValue::set(version.clone()),
+                last_version: ActiveValue::set(version.clone()),
                 ..Default::default()
             }
         }
     };
 
-    let report = report_model.save(&ctx.db).await?.try_into_model()?;
+    let report = report_model.save(db).await?.try_into_model()?;
 
     // fill log messages from latest to oldest and limit to 65 000 characters
     let mut log_messages: Vec<String> = Vec::new();
@@ -290,6 +749,32 @@ async fn ingress_background(
 
     log_messages.reverse();
 
+    let frames = if frames.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&frames)?)
+    };
+
+    let tags = sanitize_tags(event.data.tags.unwrap_or_default());
+
+    let context = match event.data.context.filter(|c| !c.is_null()) {
+        Some(context) => {
+            let context = serde_json::to_string(&context)?;
+
+            if context.len() > MAX_CONTEXT_SIZE {
+                log::warn!(
+                    "Dropping context of {} bytes for project {}",
+                    context.len(),
+                    project.project_id
+                );
+                None
+            } else {
+                Some(context)
+            }
+        }
+        None => None,
+    };
+
     // enforce backtrace limit of 10 000 characters, considering urf-8 codepoints and avoiding panics
     let backtrace = event.data.backtrace.chars().take(10000).collect::<String>();
 
@@ -297,63 +782,103 @@ async fn ingress_background(
     let event_model = project_report_events::ActiveModel {
         project_report_id: ActiveValue::set(report.project_report_id),
         backtrace: ActiveValue::set(Some(backtrace)),
+        frames: ActiveValue::set(frames),
+        project_report_merge_id: ActiveValue::set(merge_id),
+        os: ActiveValue::set(Some(truncate(&event.data.os, 64))),
+        arch: ActiveValue::set(Some(truncate(&event.data.arch, 64))),
+        version: ActiveValue::set(version),
+        thread_id: ActiveValue::set(event.data.thread_id.as_deref().map(|v| truncate(v, 64))),
+        thread_name: ActiveValue::set(event.data.thread_name.as_deref().map(|v| truncate(v, 255))),
+        location_file: ActiveValue::set(event.data.location.as_ref().map(|l| truncate(&l.file, 255))),
+        location_line: ActiveValue::set(event.data.location.as_ref().map(|l| l.line)),
+        location_column: ActiveValue::set(event.data.location.as_ref().and_then(|l| l.column)),
+        // when the event was accepted by /ingress, created is when it was processed
+        received: ActiveValue::set(Some(row.created)),
+        client_ip_hash: ActiveValue::set(row.client_ip_hash.clone()),
+        project_key_id: ActiveValue::set(row.project_key_id),
+        tags: ActiveValue::set(if tags.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&tags)?)
+        }),
+        context: ActiveValue::set(context),
         log: ActiveValue::set(Some(format!("[{}]", log_messages.join(",")))),
         ..Default::default()
     };
 
-    let event_row = event_model.insert(&ctx.db).await?;
-
-    // retain only last 5 events for this report (this may be in a separate task)
-    let events = ProjectReportEvents::find()
-        .filter(project_report_events::Column::ProjectReportId.eq(report.project_report_id))
-        .order_by_desc(project_report_events::Column::ProjectReportEventId)
-        .all(&ctx.db)
-        .await?;
-
-    let events_to_del = events
-        .iter()
-        .skip(5)
-        .map(|e| e.project_report_event_id)
-        .collect::<Vec<_>>();
-
-    if !events_to_del.is_empty() {
-        ProjectReportEvents::delete_many()
-            .filter(project_report_events::Column::ProjectReportEventId.is_in(events_to_del))
-            .exec(&ctx.db)
-            .await?;
-    }
+    let event_row = event_model.insert(db).await?;
 
     let is_new_report = matches!(report_status, Some(ReportStatus::New) | Some(ReportStatus::Regressed));
 
     // Increment counters
-    record_report_stat(&ctx.db, report.project_report_id, "event", "total_count", is_new_report).await?;
-    record_report_stat(&ctx.db, report.project_report_id, "os", &event.data.os, is_new_report).await?;
-    record_report_stat(
-        &ctx.db,
-        report.project_report_id,
-        "arch",
-        &event.data.arch,
-        is_new_report,
-    )
-    .await?;
+    record_report_stat(db, report.project_report_id, "event", "total_count", is_new_report).await?;
+    record_report_stat(db, report.project_report_id, "os", &event.data.os, is_new_report).await?;
+    record_report_stat(db, report.project_report_id, "arch", &event.data.arch, is_new_report).await?;
 
     if let Some(version) = event.data.version.as_ref() {
-        record_report_stat(&ctx.db, report.project_report_id, "version", version, is_new_report).await?;
+        record_report_stat(db, report.project_report_id, "version", version, is_new_report).await?;
+    }
+
+    for (key, value) in &tags {
+        let category = format!("tag:{}", key);
+        record_report_stat(db, report.project_report_id, &category, value, is_new_report).await?;
+    }
+
+    // ignored reports are counted, but nobody is notified about them
+    if still_ignored {
+        return Ok(None);
     }
 
-    let res = ctx.notifications.send(Notification {
+    Ok(Some(Notification {
         status: report_status,
         project,
         event: event_row,
         report,
         environment,
-    });
+    }))
+}
 
-    if let Err(e) = res {
-        log::error!("Error sending notification: {:?}", e);
+/// Checks the ignore condition of a report against a new event
+async fn is_still_ignored(
+    db: &impl ConnectionTrait,
+    report: &project_reports::Model,
+    version: Option<&str>,
+) -> Result<bool> {
+    if report.is_ignored == 0 {
+        return Ok(false);
     }
 
-    Ok(())
+    let still_ignored = match report.ignore_condition.as_deref() {
+        Some("events") => report.ignored_events_left.unwrap_or_default() > 0,
+        Some("time") => report.ignored_until.is_some_and(|until| until > Utc::now().naive_utc()),
+        Some("next_release") => match (version, report.ignored_in_version.as_deref()) {
+            (Some(version), Some(ignored_in)) => {
+                !releases::is_newer(db, report.project_id, version, ignored_in).await?
+            }
+            // ignored before any release was seen, the first versioned event is a new release
+            (Some(_), None) => false,
+            (None, _) => true,
+        },
+        _ => true,
+    };
+
+    Ok(still_ignored)
+}
+
+fn sanitize_tags(tags: BTreeMap<String, String>) -> BTreeMap<String, String> {
+    tags.into_iter()
+        .filter_map(|(key, value)| {
+            let key = truncate(key.trim(), MAX_TAG_KEY_LEN);
+
+            (!key.is_empty()).then(|| (key, truncate(&value, MAX_TAG_VALUE_LEN)))
+        })
+        .take(MAX_TAGS)
+        .collect()
+}
+
+// limit to a number of characters, considering utf-8 codepoints
+fn truncate(value: &str, max_chars: usize) -> String {
+    value.chars().take(max_chars).collect()
 }
 
 async fn notify_limit_approaching(ctx: &AppContext<'_>, org: &organizations::Model) -> Result<()> {
@@ -392,7 +917,7 @@ async fn notify_limit_approaching(ctx: &AppContext<'_>, org: &organizations::Mod
 }
 
 async fn record_report_stat(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     report_id: u32,
     category: &str,
     name: &str,
@@ -435,7 +960,7 @@ async fn record_report_stat(
     Ok(())
 }
 
-async fn record_org_stat(db: &DatabaseConnection, organization_id: u32, category: &str, name: &str) -> Result<()> {
+async fn record_org_stat(db: &impl ConnectionTrait, organization_id: u32, category: &str, name: &str) -> Result<()> {
     let stat = organization_stats::ActiveModel {
         organization_id: ActiveValue::set(organization_id),
         category: ActiveValue::set(category.into()),
@@ -465,15 +990,59 @@ async fn record_org_stat(db: &DatabaseConnection, organization_id: u32, category
     Ok(())
 }
 
+pub(crate) fn normalize_title(title: &str) -> String {
+    let mut s = title.to_lowercase();
+
+    s = Regex::new(r"[0-9a-f]{8,}")
+        .unwrap()
+        .replace_all(&s, "<hex>")
+        .into_owned();
+
+    s = Regex::new(r"\b[0-9a-f]{8}-([0-9a-f]{4}-){3}[0-9a-f]{12}\b")
+        .unwrap()
+        .replace_all(&s, "<uuid>")
+        .into_owned();
+
+    s = Regex::new(r"\b\d+\b").unwrap().replace_all(&s, "<num>").into_owned();
+
+    s = Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}")
+        .unwrap()
+        .replace_all(&s, "<email>")
+        .into_owned();
+
+    s = Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b")
+        .unwrap()
+        .replace_all(&s, "<ip>")
+        .into_owned();
+
+    s = Regex::new(r#""[^"]*"|'[^']*'"#)
+        .unwrap()
+        .replace_all(&s, "<str>")
+        .into_owned();
+
+    s = Regex::new(r"\b[a-z0-9_]*[A-Z][A-Za-z0-9_]*\b")
+        .unwrap()
+        .replace_all(&s, "<id>")
+        .into_owned();
+
+    s = Regex::new(r"\b[a-z_]+\d+[a-z0-9_]*\b")
+        .unwrap()
+        .replace_all(&s, "<id>")
+        .into_owned();
+
+    let s = Regex::new(r"\s+").unwrap().replace_all(&s, " ").trim().to_owned();
+
+    s
+}
+
 #[cfg(test)]
 mod tests {
     use actix_web::{http::StatusCode, test};
     use serde_json::Value;
-    use tokio::time::sleep;
 
     #[actix_web::test]
     async fn test_ingress_endpoint() {
-        let (app, sess) = crate::test_app_with_auth().await.unwrap();
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
 
         // create
         let req = test::TestRequest::post()
@@ -503,12 +1072,13 @@ mod tests {
         // test good request
         let req = test::TestRequest::post()
             .uri("/ingress")
+            .insert_header(("X-Forwarded-For", "203.0.113.7"))
             .set_json(serde_json::json!({
                 "key": api_key,
                 "env": "production",
                 "data": {
                     "title": "Test Error",
-                    "trace": "backtrace",
+                    "trace": "   0: core::panicking::panic\n             at /rustc/abc/library/core/src/panicking.rs:145:5\n   1: test_app::main\n             at ./src/main.rs:10:5",
                     "log": [
                         {
                             "msg": "Error message",
@@ -529,11 +1099,15 @@ mod tests {
             .to_request();
 
         let res = test::call_service(&app, req).await;
-        // let body = test::read_body(res).await;
         assert_eq!(res.status(), StatusCode::OK);
 
-        // since the ingress endpoint finishes its work in the background, we need to wait a bit before checking the results
-        sleep(tokio::time::Duration::from_millis(100)).await;
+        let ingress_res: Value = test::read_body_json(res).await;
+        assert_eq!(ingress_res["stored"], true);
+        assert_eq!(ingress_res["sample_rate"], 1.0);
+        assert_eq!(ingress_res["backoff"], 0);
+
+        // the ingress endpoint only queues the event, process it before checking the results
+        crate::process_queues(&ctx).await;
 
         // test getting reports
         let req = test::TestRequest::get()
@@ -545,6 +1119,7 @@ mod tests {
         let report_id = res["reports"][0]["report"]["project_report_id"].as_u64().unwrap();
 
         assert_eq!(res["reports"][0]["report"]["title"], "Test Error in main.rs:10");
+        assert_eq!(res["reports"][0]["report"]["uid"], ingress_res["uid"]);
         assert_eq!(res["reports"][0]["env"]["name"], "production");
 
         // get getting single report
@@ -566,50 +1141,417 @@ mod tests {
         assert!(obj.contains_key("version_dataset"));
         assert!(obj.contains_key("version_names"));
         assert!(obj.contains_key("last_event"));
+
+        let frames = &res["last_event"]["frames"];
+        assert_eq!(frames[1]["symbol"], "test_app::main");
+        assert_eq!(frames[1]["crate"], "test_app");
+        assert_eq!(frames[1]["line"], 10);
+        assert_eq!(frames[1]["in_app"], true);
+        assert_eq!(frames[0]["in_app"], false);
+
+        // the full occurrence context is stored with the event
+        let last_event = &res["last_event"];
+        assert_eq!(last_event["os"], "linux");
+        assert_eq!(last_event["version"], "1.0.0");
+        assert_eq!(last_event["location_file"], "main.rs");
+        assert_eq!(last_event["location_column"], 5);
+        assert_eq!(last_event["log"][0]["msg"], "Error message");
+        assert!(last_event["received"].is_string());
+        assert_eq!(last_event["client_ip_hash"].as_str().unwrap().len(), 64);
+        assert!(!last_event.to_string().contains("203.0.113.7"));
     }
-}
 
-fn normalize_title(title: &str) -> String {
-    let mut s = title.to_lowercase();
+    #[actix_web::test]
+    async fn test_ingress_batch_endpoint() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
 
-    s = Regex::new(r"[0-9a-f]{8,}")
-        .unwrap()
-        .replace_all(&s, "<hex>")
-        .into_owned();
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({
+                "name": "Batch Project",
+            }))
+            .to_request();
 
-    s = Regex::new(r"\b[0-9a-f]{8}-([0-9a-f]{4}-){3}[0-9a-f]{12}\b")
-        .unwrap()
-        .replace_all(&s, "<uuid>")
-        .into_owned();
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap();
 
-    s = Regex::new(r"\b\d+\b").unwrap().replace_all(&s, "<num>").into_owned();
+        let event = |title: &str| {
+            serde_json::json!({
+                "key": api_key,
+                "data": {
+                    "title": title,
+                    "trace": "backtrace",
+                    "log": [],
+                    "os": "linux",
+                    "arch": "x86_64",
+                }
+            })
+        };
 
-    s = Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}")
-        .unwrap()
-        .replace_all(&s, "<email>")
-        .into_owned();
+        let req = test::TestRequest::post()
+            .uri("/ingress/batch")
+            .set_json(serde_json::json!([
+                event("First Error"),
+                { "key": api_key },
+                { "key": "invalid", "data": event("Third Error")["data"] },
+                event("Fourth Error"),
+            ]))
+            .to_request();
 
-    s = Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b")
-        .unwrap()
-        .replace_all(&s, "<ip>")
-        .into_owned();
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let results = res["results"].as_array().unwrap();
 
-    s = Regex::new(r#""[^"]*"|'[^']*'"#)
-        .unwrap()
-        .replace_all(&s, "<str>")
-        .into_owned();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0]["accepted"], true);
+        assert_eq!(results[0]["stored"], true);
+        assert_eq!(results[1]["accepted"], false);
+        assert!(results[1]["stored"].is_null());
+        assert_eq!(results[2]["accepted"], false);
+        assert_eq!(results[3]["accepted"], true);
 
-    s = Regex::new(r"\b[a-z0-9_]*[A-Z][A-Za-z0-9_]*\b")
-        .unwrap()
-        .replace_all(&s, "<id>")
-        .into_owned();
+        crate::process_queues(&ctx).await;
 
-    s = Regex::new(r"\b[a-z_]+\d+[a-z0-9_]*\b")
-        .unwrap()
-        .replace_all(&s, "<id>")
-        .into_owned();
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports?project_id={}", project_id))
+            .cookie(sess.clone())
+            .to_request();
 
-    let s = Regex::new(r"\s+").unwrap().replace_all(&s, " ").trim().to_owned();
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 2);
+    }
 
-    s
+    #[actix_web::test]
+    async fn test_ingress_compressed_payload() {
+        use std::io::Write;
+
+        use actix_web::http::header::{ContentEncoding, ContentType, CONTENT_ENCODING};
+        use flate2::{write::GzEncoder, Compression};
+
+        let (app, sess) = crate::test_app_with_auth().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({
+                "name": "Compressed Project",
+            }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let api_key = res["api_key"].as_str().unwrap();
+
+        let event = |title: &str| {
+            serde_json::to_vec(&serde_json::json!({
+                "key": api_key,
+                "data": {
+                    "title": title,
+                    "trace": "backtrace",
+                    "log": [],
+                    "os": "linux",
+                    "arch": "x86_64",
+                }
+            }))
+            .unwrap()
+        };
+
+        let gzip = |data: &[u8]| {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap()
+        };
+
+        // gzip
+        let req = test::TestRequest::post()
+            .uri("/ingress")
+            .insert_header(ContentType::json())
+            .insert_header((CONTENT_ENCODING, ContentEncoding::Gzip))
+            .set_payload(gzip(&event("Gzip Error")))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // zstd
+        let req = test::TestRequest::post()
+            .uri("/ingress")
+            .insert_header(ContentType::json())
+            .insert_header((CONTENT_ENCODING, ContentEncoding::Zstd))
+            .set_payload(zstd::encode_all(&event("Zstd Error")[..], 0).unwrap())
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // a small compressed body that expands over the limit
+        let title = "a".repeat(5 * 1024 * 1024);
+
+        let req = test::TestRequest::post()
+            .uri("/ingress")
+            .insert_header(ContentType::json())
+            .insert_header((CONTENT_ENCODING, ContentEncoding::Gzip))
+            .set_payload(gzip(&event(&title)))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let body: Value = test::read_body_json(res).await;
+        assert_eq!(body["user"]["type"], "payload_too_large");
+    }
+
+    #[actix_web::test]
+    async fn test_frames_grouping() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({
+                "name": "Frames Project",
+                "grouping": "frames",
+            }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap();
+
+        let event = |title: &str, function: &str| {
+            serde_json::json!({
+                "key": api_key,
+                "data": {
+                    "title": title,
+                    "trace": format!("   0: core::panicking::panic\n   1: app::{}\n             at ./src/lib.rs:5:1\n   2: app::main", function),
+                    "log": [],
+                    "os": "linux",
+                    "arch": "x86_64",
+                    "loc": { "f": "src/helpers.rs", "l": 10 }
+                }
+            })
+        };
+
+        // same frames with different messages are grouped together, a different caller gets its own report
+        let req = test::TestRequest::post()
+            .uri("/ingress/batch")
+            .set_json(serde_json::json!([
+                event("Order 1 not found", "load_order"),
+                event("Connection reset by peer", "load_order"),
+                event("Request timed out", "load_user"),
+            ]))
+            .to_request();
+
+        test::call_service(&app, req).await;
+
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports?project_id={}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_custom_fingerprint() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Fingerprint Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap();
+
+        let event = |title: &str, fingerprint: Value| {
+            serde_json::json!({
+                "key": api_key,
+                "data": {
+                    "title": title,
+                    "trace": "",
+                    "log": [],
+                    "os": "linux",
+                    "arch": "x86_64",
+                    "fingerprint": fingerprint
+                }
+            })
+        };
+
+        // the first two share a fingerprint, the default placeholder keeps title grouping within a fingerprint
+        let req = test::TestRequest::post()
+            .uri("/ingress/batch")
+            .set_json(serde_json::json!([
+                event("Database unavailable", serde_json::json!(["database"])),
+                event("Connection refused", serde_json::json!(["database"])),
+                event("Connection refused", serde_json::json!(["{{ default }}", "worker"])),
+                event("Connection refused", serde_json::json!(["{{ default }}", "worker"])),
+                event("Connection refused", Value::Null),
+            ]))
+            .to_request();
+
+        test::call_service(&app, req).await;
+
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports?project_id={}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 3);
+    }
+
+    #[actix_web::test]
+    async fn test_report_rate_limit() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Rate Limit Project", "report_rate_limit": 2 }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap();
+
+        let event = |title: &str| {
+            serde_json::json!({
+                "key": api_key,
+                "data": {
+                    "title": title,
+                    "trace": "",
+                    "log": [],
+                    "os": "linux",
+                    "arch": "x86_64"
+                }
+            })
+        };
+
+        // a crash loop only gets the first events of each minute stored
+        let req = test::TestRequest::post()
+            .uri("/ingress/batch")
+            .set_json(serde_json::json!([
+                event("Crash loop"),
+                event("Crash loop"),
+                event("Crash loop"),
+                event("Crash loop"),
+                event("Database unavailable"),
+            ]))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let stored: Vec<bool> = res["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["stored"].as_bool().unwrap())
+            .collect();
+        assert_eq!(stored, [true, true, false, false, true]);
+
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports?project_id={}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let reports = res["reports"].as_array().unwrap();
+        assert_eq!(reports.len(), 2);
+
+        let crash_loop = reports
+            .iter()
+            .find(|r| r["report"]["title"].as_str().unwrap().starts_with("Crash loop"))
+            .unwrap();
+
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/api/reports/{}/events",
+                crash_loop["report"]["project_report_id"]
+            ))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["events"].as_array().unwrap().len(), 2);
+
+        let req = test::TestRequest::get()
+            .uri("/api/organizations/1/stats?grouping=daily&category=rate_limited")
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let today = res["dataset"].as_array().unwrap().last().unwrap().clone();
+        assert_eq!(today["report"], 2);
+    }
+
+    #[actix_web::test]
+    async fn test_requests_limit() {
+        use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait, PaginatorTrait};
+
+        use crate::entity::organizations;
+        use crate::entity::prelude::*;
+
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        organizations::ActiveModel {
+            organization_id: ActiveValue::unchanged(1),
+            requests_limit: ActiveValue::set(Some(1)),
+            ..Default::default()
+        }
+        .update(&ctx.db)
+        .await
+        .unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Limited Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let api_key = res["api_key"].as_str().unwrap();
+
+        for title in ["First Error", "Second Error"] {
+            let req = test::TestRequest::post()
+                .uri("/ingress")
+                .set_json(serde_json::json!({
+                    "key": api_key,
+                    "data": {
+                        "title": title,
+                        "trace": "",
+                        "log": [],
+                        "os": "linux",
+                        "arch": "x86_64"
+                    }
+                }))
+                .to_request();
+
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+
+        crate::process_queues(&ctx).await;
+
+        // the event over the limit is counted and dropped instead of staying in the queue
+        assert_eq!(QueuedEvents::find().count(&ctx.db).await.unwrap(), 0);
+        assert_eq!(ProjectReports::find().count(&ctx.db).await.unwrap(), 1);
+
+        let req = test::TestRequest::get()
+            .uri("/api/organizations/1/stats?grouping=daily&category=dropped")
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let today = res["dataset"].as_array().unwrap().last().unwrap().clone();
+        assert_eq!(today["requests_limit"], 1);
+    }
 }
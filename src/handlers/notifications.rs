@@ -11,11 +11,14 @@ use serde_json::json;
 
 use crate::entity::organization_users;
 use crate::entity::prelude::*;
+use crate::entity::project_environments;
 use crate::entity::project_user_settings;
 use crate::entity::users;
 
 use crate::{AppContext, Error, Identity, Result};
 
+mod channels;
+mod deliveries;
 mod slack_app;
 mod slack_webhook;
 mod teams_webhook;
@@ -25,6 +28,8 @@ pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg.service(per_user_notifications)
         .service(per_user_save)
         .service(get_project)
+        .service(web::scope("/{project_id}/channels").configure(channels::routes))
+        .service(web::scope("/{project_id}/deliveries").configure(deliveries::routes))
         .service(web::scope("/{project_id}/slack-app").configure(slack_app::routes))
         .service(web::scope("/{project_id}/slack-webhook").configure(slack_webhook::routes))
         .service(web::scope("/{project_id}/teams-webhook").configure(teams_webhook::routes))
@@ -161,10 +166,47 @@ async fn get_project(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -
         .await?
         .ok_or(Error::LoginRequired)?;
 
-    let environments = project.find_related(ProjectEnvironments).all(&ctx.db).await?;
+    // events of merged environments are notified with the environment they were merged into
+    let environments = project
+        .find_related(ProjectEnvironments)
+        .filter(project_environments::Column::MergedIntoId.is_null())
+        .all(&ctx.db)
+        .await?;
+
+    let channels = project.find_related(ProjectNotificationChannels).all(&ctx.db).await?;
 
     let mut res = serde_json::json!(project);
-    res["environments"] = serde_json::json!(environments);
+    let mut environments = serde_json::json!(environments);
+
+    // the integration forms read channel settings in the shape of the project columns they replaced
+    for row in channels {
+        let config: serde_json::Value = serde_json::from_str(&row.config).unwrap_or_default();
+
+        let (key, value) = match row.channel.as_str() {
+            "slack" => ("slack_channel", config["channel"].clone()),
+            name => (name, config["url"].clone()),
+        };
+
+        let Some(environment_id) = row.project_environment_id else {
+            if row.channel == "slack" {
+                res["slack_bot_token"] = (!config["bot_token"].is_null()).into();
+            }
+
+            res[key] = value;
+            continue;
+        };
+
+        let environment = environments.as_array_mut().and_then(|envs| {
+            envs.iter_mut()
+                .find(|env| env["project_environment_id"] == environment_id)
+        });
+
+        if let Some(environment) = environment {
+            environment[key] = if row.is_enabled > 0 { value } else { "-1".into() };
+        }
+    }
+
+    res["environments"] = environments;
 
     Ok(Json(res))
 }
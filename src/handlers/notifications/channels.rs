@@ -0,0 +1,610 @@
+use actix_web::{
+    get, post,
+    web::{self, Data, Json, Path},
+    Responder,
+};
+use sea_orm::{prelude::*, ActiveValue, IntoActiveModel, QueryOrder, QuerySelect, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::entity::prelude::*;
+use crate::entity::project_environments;
+use crate::entity::project_notification_channels;
+use crate::entity::projects;
+
+use crate::handlers::organizations;
+use crate::notifications::{self, merge_config, NotificationChannel};
+use crate::{AppContext, Error, Identity, Result};
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list).service(save).service(delete).service(test);
+}
+
+#[derive(Deserialize)]
+struct EnvironmentInput {
+    project_environment_id: u32,
+    /// Replaces parts of the project configuration for this environment
+    config: Option<Value>,
+    is_enabled: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct ChannelInput {
+    config: Value,
+    is_enabled: Option<bool>,
+    #[serde(default)]
+    environments: Vec<EnvironmentInput>,
+}
+
+#[derive(Serialize)]
+struct EnvironmentSettings {
+    project_environment_id: u32,
+    config: Value,
+    is_enabled: bool,
+}
+
+#[derive(Serialize)]
+struct ChannelSettings {
+    channel: String,
+    config: Value,
+    is_enabled: bool,
+    environments: Vec<EnvironmentSettings>,
+}
+
+/// Environment specific settings of a channel
+pub(super) struct EnvironmentOverride {
+    pub project_environment_id: u32,
+    pub config: Value,
+    pub is_enabled: bool,
+}
+
+impl EnvironmentOverride {
+    /// Reads an environment setting of the integration forms, where "-1" turns the channel off
+    /// for the environment and a missing value uses the project configuration
+    pub(super) fn from_setting(project_environment_id: u32, key: &str, setting: Option<String>) -> Option<Self> {
+        let setting = setting.filter(|setting| !setting.is_empty())?;
+
+        Some(if setting == "-1" {
+            Self {
+                project_environment_id,
+                config: json!({}),
+                is_enabled: false,
+            }
+        } else {
+            Self {
+                project_environment_id,
+                config: json!({ key: setting }),
+                is_enabled: true,
+            }
+        })
+    }
+}
+
+/// Any member of the project's organization can manage its notification channels
+pub(super) async fn find_project(ctx: &AppContext<'_>, id: &Identity, project_id: u32) -> Result<projects::Model> {
+    let organization_id: u32 = Projects::find_by_id(project_id)
+        .select_only()
+        .column(projects::Column::OrganizationId)
+        .into_tuple()
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    organizations::find_project(ctx, id, organization_id, project_id, false).await
+}
+
+fn find_channel(name: &str) -> Result<&'static dyn NotificationChannel> {
+    notifications::channel(name).ok_or(Error::NotFound)
+}
+
+/// Project wide configuration of a channel
+pub(super) async fn channel_config(
+    ctx: &AppContext<'_>,
+    project_id: u32,
+    channel: &str,
+) -> Result<Option<(Value, project_notification_channels::Model)>> {
+    let row = ProjectNotificationChannels::find()
+        .filter(project_notification_channels::Column::ProjectId.eq(project_id))
+        .filter(project_notification_channels::Column::ProjectEnvironmentId.is_null())
+        .filter(project_notification_channels::Column::Channel.eq(channel))
+        .one(&ctx.db)
+        .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let config = serde_json::from_str(&row.config)?;
+
+    Ok(Some((config, row)))
+}
+
+/// Saves the project configuration of a channel. Environment overrides are replaced when given.
+pub(super) async fn save_channel(
+    ctx: &AppContext<'_>,
+    project: &projects::Model,
+    channel: &dyn NotificationChannel,
+    config: Value,
+    is_enabled: bool,
+    environments: Option<Vec<EnvironmentOverride>>,
+) -> Result<()> {
+    channel
+        .validate(&config)
+        .map_err(|e| Error::field("config", e.to_string().into()))?;
+
+    if let Some(environments) = environments.as_ref() {
+        let environment_ids: Vec<u32> = project
+            .find_related(ProjectEnvironments)
+            .all(&ctx.db)
+            .await?
+            .into_iter()
+            .map(|environment| environment.project_environment_id)
+            .collect();
+
+        for environment in environments {
+            if !environment_ids.contains(&environment.project_environment_id) {
+                return Err(Error::field("environments", "Environment not found".into()));
+            }
+
+            channel
+                .validate(&merge_config(&config, &environment.config))
+                .map_err(|e| Error::field("environments", e.to_string().into()))?;
+        }
+    }
+
+    let existing = channel_config(ctx, project.project_id, channel.name()).await?;
+
+    let txn = ctx.db.begin().await?;
+
+    let mut row = match existing {
+        Some((_, row)) => row.into_active_model(),
+        None => project_notification_channels::ActiveModel {
+            project_id: ActiveValue::set(project.project_id),
+            channel: ActiveValue::set(channel.name().to_string()),
+            ..Default::default()
+        },
+    };
+
+    row.config = ActiveValue::set(config.to_string());
+    row.is_enabled = ActiveValue::set(is_enabled.into());
+    row.save(&txn).await?;
+
+    if let Some(environments) = environments {
+        ProjectNotificationChannels::delete_many()
+            .filter(project_notification_channels::Column::ProjectId.eq(project.project_id))
+            .filter(project_notification_channels::Column::ProjectEnvironmentId.is_not_null())
+            .filter(project_notification_channels::Column::Channel.eq(channel.name()))
+            .exec(&txn)
+            .await?;
+
+        for environment in environments {
+            let row = project_notification_channels::ActiveModel {
+                project_id: ActiveValue::set(project.project_id),
+                project_environment_id: ActiveValue::set(Some(environment.project_environment_id)),
+                channel: ActiveValue::set(channel.name().to_string()),
+                config: ActiveValue::set(environment.config.to_string()),
+                is_enabled: ActiveValue::set(environment.is_enabled.into()),
+                ..Default::default()
+            };
+
+            row.insert(&txn).await?;
+        }
+    }
+
+    txn.commit().await?;
+
+    Ok(())
+}
+
+/// Removes a channel from the project, including its environment overrides
+pub(super) async fn delete_channel(ctx: &AppContext<'_>, project_id: u32, channel: &str) -> Result<()> {
+    ProjectNotificationChannels::delete_many()
+        .filter(project_notification_channels::Column::ProjectId.eq(project_id))
+        .filter(project_notification_channels::Column::Channel.eq(channel))
+        .exec(&ctx.db)
+        .await?;
+
+    Ok(())
+}
+
+/// Sends a test message with the project configuration of a channel
+pub(super) async fn test_channel(
+    ctx: &AppContext<'_>,
+    project: &projects::Model,
+    channel: &dyn NotificationChannel,
+) -> Result<()> {
+    let Some((config, _)) = channel_config(ctx, project.project_id, channel.name()).await? else {
+        return Err(Error::new("This channel is not configured"));
+    };
+
+    channel
+        .send_test(ctx, &config, project)
+        .await
+        .map_err(|e| Error::new(format!("Test message failed: {}", e)))?;
+
+    Ok(())
+}
+
+// secrets, like access tokens, are only shown as set
+fn redact(channel: Option<&dyn NotificationChannel>, mut config: Value) -> Value {
+    for key in channel.map(|c| c.secret_keys()).unwrap_or_default() {
+        if let Some(value) = config.get_mut(*key).filter(|value| !value.is_null()) {
+            *value = true.into();
+        }
+    }
+
+    config
+}
+
+#[get("")]
+async fn list(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
+    let project = find_project(&ctx, &id, path.into_inner()).await?;
+
+    let rows = project
+        .find_related(ProjectNotificationChannels)
+        .order_by_asc(project_notification_channels::Column::ProjectNotificationChannelId)
+        .all(&ctx.db)
+        .await?;
+
+    let parse = |row: &project_notification_channels::Model| {
+        let config = serde_json::from_str(&row.config).unwrap_or_default();
+        redact(notifications::channel(&row.channel), config)
+    };
+
+    let channels: Vec<ChannelSettings> = rows
+        .iter()
+        .filter(|row| row.project_environment_id.is_none())
+        .map(|row| ChannelSettings {
+            channel: row.channel.clone(),
+            config: parse(row),
+            is_enabled: row.is_enabled > 0,
+            environments: rows
+                .iter()
+                .filter(|env_row| env_row.channel == row.channel)
+                .filter_map(|env_row| {
+                    Some(EnvironmentSettings {
+                        project_environment_id: env_row.project_environment_id?,
+                        config: parse(env_row),
+                        is_enabled: env_row.is_enabled > 0,
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+
+    let available: Vec<&str> = notifications::CHANNELS.iter().map(|c| c.name()).collect();
+
+    let environments = project
+        .find_related(ProjectEnvironments)
+        .filter(project_environments::Column::MergedIntoId.is_null())
+        .all(&ctx.db)
+        .await?;
+
+    Ok(Json(json!({
+        "available": available,
+        "channels": channels,
+        "environments": environments,
+    })))
+}
+
+#[post("/{channel}")]
+async fn save(
+    ctx: Data<AppContext<'_>>,
+    id: Identity,
+    path: Path<(u32, String)>,
+    input: Json<ChannelInput>,
+) -> Result<impl Responder> {
+    let (project_id, channel_name) = path.into_inner();
+
+    let channel = find_channel(&channel_name)?;
+    let project = find_project(&ctx, &id, project_id).await?;
+    let input = input.into_inner();
+
+    let mut config = input.config;
+
+    if !config.is_object() {
+        return Err(Error::field("config", "Invalid configuration".into()));
+    }
+
+    let existing = project
+        .find_related(ProjectNotificationChannels)
+        .filter(project_notification_channels::Column::Channel.eq(channel.name()))
+        .all(&ctx.db)
+        .await?;
+
+    let existing_config = |project_environment_id: Option<u32>| {
+        existing
+            .iter()
+            .find(|row| row.project_environment_id == project_environment_id)
+            .and_then(|row| serde_json::from_str::<Value>(&row.config).ok())
+    };
+
+    // redacted secrets are sent back as they were listed
+    if let Some(existing) = existing_config(None) {
+        for key in channel.secret_keys() {
+            if config.get(*key).is_none_or(|value| value.is_boolean()) {
+                config[*key] = existing.get(*key).cloned().unwrap_or_default();
+            }
+        }
+    }
+
+    let mut environments = Vec::new();
+
+    for environment in input.environments {
+        let mut config = environment.config.unwrap_or_else(|| json!({}));
+
+        if !config.is_object() {
+            return Err(Error::field("environments", "Invalid configuration".into()));
+        }
+
+        // overrides only list the secrets they replace
+        let existing = existing_config(Some(environment.project_environment_id)).unwrap_or_default();
+
+        for key in channel.secret_keys() {
+            if config.get(*key).is_some_and(|value| value.is_boolean()) {
+                config[*key] = existing.get(*key).cloned().unwrap_or_default();
+            }
+        }
+
+        environments.push(EnvironmentOverride {
+            project_environment_id: environment.project_environment_id,
+            config,
+            is_enabled: environment.is_enabled.unwrap_or(true),
+        });
+    }
+
+    save_channel(
+        &ctx,
+        &project,
+        channel,
+        config,
+        input.is_enabled.unwrap_or(true),
+        Some(environments),
+    )
+    .await?;
+
+    Ok(Json(()))
+}
+
+#[post("/{channel}/delete")]
+async fn delete(ctx: Data<AppContext<'_>>, id: Identity, path: Path<(u32, String)>) -> Result<impl Responder> {
+    let (project_id, channel_name) = path.into_inner();
+
+    let channel = find_channel(&channel_name)?;
+    let project = find_project(&ctx, &id, project_id).await?;
+
+    delete_channel(&ctx, project.project_id, channel.name()).await?;
+
+    Ok(Json(()))
+}
+
+#[post("/{channel}/test")]
+async fn test(ctx: Data<AppContext<'_>>, id: Identity, path: Path<(u32, String)>) -> Result<impl Responder> {
+    let (project_id, channel_name) = path.into_inner();
+
+    let channel = find_channel(&channel_name)?;
+    let project = find_project(&ctx, &id, project_id).await?;
+
+    test_channel(&ctx, &project, channel).await?;
+
+    Ok(Json(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test;
+    use sea_orm::{prelude::*, QueryOrder};
+    use serde_json::Value;
+
+    use crate::entity::prelude::*;
+    use crate::entity::project_notification_channels;
+
+    #[actix_web::test]
+    async fn test_notification_channels() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Channels Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/organizations/1/projects/{}/environments", project_id))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "production" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let environment_id = res["project_environment_id"].as_u64().unwrap();
+
+        let channels_uri = format!("/api/notifications/{}/channels", project_id);
+
+        // channels validate their configuration
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/webhook", channels_uri))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "config": { "url": "not a url" } }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/carrier-pigeon", channels_uri))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "config": {} }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 404);
+
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/webhook", channels_uri))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({
+                "config": { "url": "https://example.com/hook" },
+                "environments": [{ "project_environment_id": environment_id, "is_enabled": false }]
+            }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+
+        let req = test::TestRequest::get()
+            .uri(&channels_uri)
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let channels = res["channels"].as_array().unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0]["channel"], "webhook");
+        assert_eq!(channels[0]["config"]["url"], "https://example.com/hook");
+        assert_eq!(channels[0]["environments"][0]["is_enabled"], false);
+
+        // the integration forms still read the settings from the project
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/notifications/project/{}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["webhook"], "https://example.com/hook");
+        assert_eq!(res["environments"][0]["webhook"], "-1");
+
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/webhook/delete", channels_uri))
+            .cookie(sess.clone())
+            .to_request();
+
+        test::call_service(&app, req).await;
+
+        let req = test::TestRequest::get()
+            .uri(&channels_uri)
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert!(res["channels"].as_array().unwrap().is_empty());
+
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/telegram", channels_uri))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "config": { "bot_token": "not a token", "chat_id": "-100123" } }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        // environments can post to another chat with the same bot
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/telegram", channels_uri))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({
+                "config": { "bot_token": "123456:ABC-def_123", "chat_id": "-100123" },
+                "environments": [{ "project_environment_id": environment_id, "config": { "chat_id": "@alerts" } }]
+            }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/matrix", channels_uri))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({
+                "config": { "homeserver": "https://matrix.org", "access_token": "syt_token", "room_id": "#alerts:matrix.org" }
+            }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/discord", channels_uri))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "config": { "url": "https://discord.com/api/webhooks/1/token" } }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+
+        let req = test::TestRequest::get()
+            .uri(&channels_uri)
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert!(res["available"].as_array().unwrap().contains(&"matrix".into()));
+
+        let channels = res["channels"].as_array().unwrap();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0]["channel"], "telegram");
+        assert_eq!(channels[0]["config"]["bot_token"], true);
+        assert_eq!(channels[0]["environments"][0]["config"]["chat_id"], "@alerts");
+        assert_eq!(channels[1]["channel"], "discord");
+
+        // configurations have to be objects
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/telegram", channels_uri))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "config": "bot_token" }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        // redacted secrets of environments are kept when saved as listed
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/telegram", channels_uri))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({
+                "config": { "bot_token": "123456:ABC-def_123", "chat_id": "-100123" },
+                "environments": [{
+                    "project_environment_id": environment_id,
+                    "config": { "bot_token": "654321:XYZ-def_123", "chat_id": "@alerts" }
+                }]
+            }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+
+        let req = test::TestRequest::get()
+            .uri(&channels_uri)
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let telegram = res["channels"][0].clone();
+        assert_eq!(telegram["environments"][0]["config"]["bot_token"], true);
+
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/telegram", channels_uri))
+            .cookie(sess.clone())
+            .set_json(telegram)
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+
+        let rows = ProjectNotificationChannels::find()
+            .filter(project_notification_channels::Column::ProjectId.eq(project_id as u32))
+            .filter(project_notification_channels::Column::Channel.eq("telegram"))
+            .order_by_asc(project_notification_channels::Column::ProjectNotificationChannelId)
+            .all(&ctx.db)
+            .await
+            .unwrap();
+
+        let configs: Vec<Value> = rows
+            .iter()
+            .map(|row| serde_json::from_str(&row.config).unwrap())
+            .collect();
+        assert_eq!(configs[0]["bot_token"], "123456:ABC-def_123");
+        assert_eq!(configs[1]["bot_token"], "654321:XYZ-def_123");
+    }
+}
@@ -0,0 +1,231 @@
+use actix_web::{
+    get, post,
+    web::{self, Data, Json, Path, Query},
+    Responder,
+};
+use sea_orm::{prelude::*, QueryOrder, QuerySelect};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::entity::prelude::*;
+use crate::entity::project_notification_deliveries;
+use crate::notifications::outbox;
+use crate::{AppContext, Error, Identity, Result};
+
+use super::channels::find_project;
+
+const PAGE_SIZE: u64 = 50;
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list).service(resend);
+}
+
+#[derive(Deserialize)]
+struct DeliveriesQuery {
+    status: Option<String>,
+    /// Id of the last delivery of the previous page
+    before: Option<u32>,
+}
+
+#[get("")]
+async fn list(
+    ctx: Data<AppContext<'_>>,
+    id: Identity,
+    path: Path<u32>,
+    query: Query<DeliveriesQuery>,
+) -> Result<impl Responder> {
+    let project = find_project(&ctx, &id, path.into_inner()).await?;
+
+    let mut select = project
+        .find_related(ProjectNotificationDeliveries)
+        .order_by_desc(project_notification_deliveries::Column::ProjectNotificationDeliveryId)
+        .limit(PAGE_SIZE + 1);
+
+    if let Some(status) = query.status.as_ref() {
+        select = select.filter(project_notification_deliveries::Column::Status.eq(status));
+    }
+
+    if let Some(before) = query.before {
+        select = select.filter(project_notification_deliveries::Column::ProjectNotificationDeliveryId.lt(before));
+    }
+
+    let mut deliveries = select.all(&ctx.db).await?;
+
+    let next = if deliveries.len() as u64 > PAGE_SIZE {
+        deliveries.truncate(PAGE_SIZE as usize);
+        deliveries.last().map(|d| d.project_notification_delivery_id)
+    } else {
+        None
+    };
+
+    Ok(Json(json!({
+        "deliveries": deliveries,
+        "next": next,
+    })))
+}
+
+#[post("/{delivery_id}/resend")]
+async fn resend(ctx: Data<AppContext<'_>>, id: Identity, path: Path<(u32, u32)>) -> Result<impl Responder> {
+    let (project_id, delivery_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, project_id).await?;
+
+    let delivery = ProjectNotificationDeliveries::find_by_id(delivery_id)
+        .filter(project_notification_deliveries::Column::ProjectId.eq(project.project_id))
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if delivery.status != outbox::STATUS_FAILED {
+        return Err(Error::new("Only failed notifications can be resent"));
+    }
+
+    let delivery = outbox::resend(&ctx, delivery)
+        .await?
+        .ok_or_else(|| Error::new("Only failed notifications can be resent"))?;
+
+    Ok(Json(delivery))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test;
+    use sea_orm::prelude::*;
+    use serde_json::Value;
+
+    use crate::entity::prelude::*;
+    use crate::entity::project_notification_deliveries;
+    use crate::notifications::outbox;
+
+    #[actix_web::test]
+    async fn test_notification_deliveries() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Deliveries Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        // nothing listens on this port, so every attempt fails
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/notifications/{}/channels/webhook", project_id))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "config": { "url": "http://127.0.0.1:1/hook" } }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+
+        let req = test::TestRequest::post()
+            .uri("/ingress")
+            .set_json(serde_json::json!({
+                "key": api_key,
+                "data": {
+                    "title": "Delivery failure",
+                    "trace": "",
+                    "log": [],
+                    "os": "linux",
+                    "arch": "x86_64",
+                    "ver": "1.0.0"
+                }
+            }))
+            .to_request();
+
+        test::call_service(&app, req).await;
+        crate::process_queues(&ctx).await;
+
+        let deliveries_uri = format!("/api/notifications/{}/deliveries", project_id);
+
+        let req = test::TestRequest::get()
+            .uri(&deliveries_uri)
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert!(res["next"].is_null());
+
+        // the project creator is also notified by email
+        let deliveries = res["deliveries"].as_array().unwrap();
+        assert_eq!(deliveries.len(), 2);
+
+        let delivery = deliveries.iter().find(|d| d["channel"] == "webhook").unwrap();
+        assert_eq!(delivery["status"], "pending");
+        assert_eq!(delivery["attempts"], 1);
+        assert!(delivery["error"].is_string());
+        let delivery_id = delivery["project_notification_delivery_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::get()
+            .uri(&format!("{}?status=delivered", deliveries_uri))
+            .cookie(sess.clone())
+            .to_request();
+
+        // without a mailer emails are delivered without sending anything
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let deliveries = res["deliveries"].as_array().unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0]["channel"], "email");
+        let email_delivery_id = deliveries[0]["project_notification_delivery_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}/resend", deliveries_uri, email_delivery_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        // pending deliveries are still retried by the worker
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}/resend", deliveries_uri, delivery_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        ProjectNotificationDeliveries::update_many()
+            .col_expr(
+                project_notification_deliveries::Column::Status,
+                Expr::value(outbox::STATUS_FAILED),
+            )
+            .filter(project_notification_deliveries::Column::ProjectNotificationDeliveryId.eq(delivery_id as u32))
+            .exec(&ctx.db)
+            .await
+            .unwrap();
+
+        // resending starts over with a new set of attempts
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}/resend", deliveries_uri, delivery_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["status"], "pending");
+        assert_eq!(res["attempts"], 0);
+
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&deliveries_uri)
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let deliveries = res["deliveries"].as_array().unwrap();
+        let delivery = deliveries.iter().find(|d| d["channel"] == "webhook").unwrap();
+        assert_eq!(delivery["attempts"], 1);
+
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/999999/resend", deliveries_uri))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 404);
+    }
+}
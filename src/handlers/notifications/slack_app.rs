@@ -3,15 +3,20 @@ use actix_web::{
     web::{self, Data, Json, Path},
     Responder,
 };
-use sea_orm::{prelude::*, ActiveValue, IntoActiveModel};
+use sea_orm::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use validator::Validate;
 
+use super::channels::{self, EnvironmentOverride};
 use crate::entity::prelude::*;
+use crate::entity::project_environments;
 
+use crate::notifications::{self, merge_config};
 use crate::{AppContext, Error, Identity, Result};
 
+const CHANNEL: &str = "slack";
+
 pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg.service(config)
         .service(config_save)
@@ -48,7 +53,10 @@ async fn config(ctx: Data<AppContext<'_>>, path: Path<u32>, id: Identity) -> Res
 
     let mut chats = vec![];
 
-    if let Some(bot_token) = project.slack_bot_token.as_deref() {
+    let slack = channels::channel_config(&ctx, project.project_id, CHANNEL).await?;
+    let bot_token = slack.as_ref().and_then(|(settings, _)| settings["bot_token"].as_str());
+
+    if let Some(bot_token) = bot_token {
         let params = [
             ("token", bot_token),
             ("limit", "1000"),
@@ -74,7 +82,12 @@ async fn config(ctx: Data<AppContext<'_>>, path: Path<u32>, id: Identity) -> Res
         ctx.config.scheme, ctx.config.base_url, project.project_id
     );
 
-    let environments = project.find_related(ProjectEnvironments).all(&ctx.db).await?;
+    // events of merged environments are notified with the environment they were merged into
+    let environments = project
+        .find_related(ProjectEnvironments)
+        .filter(project_environments::Column::MergedIntoId.is_null())
+        .all(&ctx.db)
+        .await?;
 
     // let redirect_uri = "https://localhost:5173/reports/notifications?project_id=1";
 
@@ -159,9 +172,14 @@ async fn config_save(
         return Err(Error::Internal(anyhow::anyhow!("Error from slack: {:?}", result.error)));
     }
 
-    let mut project_model = project.into_active_model();
-    project_model.slack_bot_token = ActiveValue::set(result.access_token);
-    project_model.save(&ctx.db).await?;
+    // the channel is picked after the app is added
+    let settings = match channels::channel_config(&ctx, project.project_id, CHANNEL).await? {
+        Some((settings, _)) => merge_config(&settings, &json!({ "bot_token": result.access_token })),
+        None => json!({ "bot_token": result.access_token }),
+    };
+
+    let channel = notifications::channel(CHANNEL).expect("slack channel is registered");
+    channels::save_channel(&ctx, &project, channel, settings, true, None).await?;
 
     Ok(Json(()))
 }
@@ -189,93 +207,41 @@ async fn save(
     input.validate()?;
     let input = input.into_inner();
 
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
 
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
+    let Some((settings, _)) = channels::channel_config(&ctx, project.project_id, CHANNEL).await? else {
+        return Err(Error::new("Slack App not configured"));
+    };
 
-    let mut project_model = project.into_active_model();
-    project_model.slack_channel = ActiveValue::set(Some(input.slack_channel));
-    project_model.save(&ctx.db).await?;
+    let settings = merge_config(&settings, &json!({ "channel": input.slack_channel }));
 
-    for env_input in input.environments {
-        let Some(env) = ProjectEnvironments::find_by_id(env_input.project_environment_id)
-            .one(&ctx.db)
-            .await?
-        else {
-            continue;
-        };
+    let environments = input
+        .environments
+        .into_iter()
+        .filter_map(|env| EnvironmentOverride::from_setting(env.project_environment_id, "channel", env.slack_channel))
+        .collect();
 
-        let mut env = env.into_active_model();
-        env.slack_channel = ActiveValue::set(env_input.slack_channel);
-        env.save(&ctx.db).await?;
-    }
+    let channel = notifications::channel(CHANNEL).expect("slack channel is registered");
+    channels::save_channel(&ctx, &project, channel, settings, true, Some(environments)).await?;
 
     Ok(Json(()))
 }
 
 #[post("/delete")]
 async fn delete(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
 
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
-
-    let mut project_model = project.into_active_model();
-    project_model.slack_bot_token = ActiveValue::set(None);
-    project_model.slack_channel = ActiveValue::set(None);
-    project_model.save(&ctx.db).await?;
+    channels::delete_channel(&ctx, project.project_id, CHANNEL).await?;
 
     Ok(Json(()))
 }
 
 #[post("/test")]
 async fn test(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
 
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
-
-    let Some((token, channel)) = project.slack_bot_token.zip(project.slack_channel) else {
-        return Err(Error::new("Slack App not configured"));
-    };
-
-    let params = [
-        ("token", token),
-        ("channel", channel),
-        (
-            "text",
-            "You have successfully configured Slack App for notifications!".into(),
-        ),
-    ];
-
-    let client = reqwest::Client::new();
-    client
-        .post("https://slack.com/api/chat.postMessage")
-        .form(&params)
-        .send()
-        .await?;
+    let channel = notifications::channel(CHANNEL).expect("slack channel is registered");
+    channels::test_channel(&ctx, &project, channel).await?;
 
     Ok(Json(()))
 }
@@ -1,17 +1,16 @@
-use std::collections::HashMap;
-
 use actix_web::{
     post,
     web::{self, Data, Json, Path},
     Responder,
 };
-use sea_orm::{prelude::*, ActiveValue, IntoActiveModel};
 use serde::Deserialize;
 use validator::Validate;
 
-use crate::entity::prelude::*;
+use super::channels::{self, EnvironmentOverride};
+use crate::notifications;
+use crate::{AppContext, Identity, Result};
 
-use crate::{AppContext, Error, Identity, Result};
+const CHANNEL: &str = "slack_webhook";
 
 pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg.service(save).service(delete).service(test);
@@ -40,90 +39,37 @@ async fn save(
     input.validate()?;
     let input = input.into_inner();
 
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
-
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
-
-    let mut project_model = project.into_active_model();
-    project_model.slack_webhook = ActiveValue::set(Some(input.webhook_url));
-    project_model.save(&ctx.db).await?;
-
-    for env_input in input.environments {
-        let Some(env) = ProjectEnvironments::find_by_id(env_input.project_environment_id)
-            .one(&ctx.db)
-            .await?
-        else {
-            continue;
-        };
-
-        let mut env = env.into_active_model();
-        env.slack_webhook = ActiveValue::set(env_input.slack_webhook);
-        env.save(&ctx.db).await?;
-    }
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
+
+    let environments = input
+        .environments
+        .into_iter()
+        .filter_map(|env| EnvironmentOverride::from_setting(env.project_environment_id, "url", env.slack_webhook))
+        .collect();
+
+    let channel = notifications::channel(CHANNEL).expect("slack_webhook channel is registered");
+    let config = serde_json::json!({ "url": input.webhook_url });
+
+    channels::save_channel(&ctx, &project, channel, config, true, Some(environments)).await?;
 
     Ok(Json(()))
 }
 
 #[post("/delete")]
 async fn delete(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
-
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
-
-    let mut project_model = project.into_active_model();
-    project_model.slack_webhook = ActiveValue::set(None);
-    project_model.save(&ctx.db).await?;
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
+
+    channels::delete_channel(&ctx, project.project_id, CHANNEL).await?;
 
     Ok(Json(()))
 }
 
 #[post("/test")]
 async fn test(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
-
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
-
-    let Some(webhook_url) = project.slack_webhook else {
-        return Err(Error::new("Slack Webhook URL is not set"));
-    };
-
-    let mut params = HashMap::new();
-    params.insert(
-        "text",
-        format!(
-            "Slack is working! I'll post here when project {} panic!()s",
-            project.name
-        ),
-    );
-
-    let client = reqwest::Client::new();
-    let res = client.post(webhook_url).json(&params).send().await?;
-
-    log::info!("Slack test response: {:?}", res.text().await);
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
+
+    let channel = notifications::channel(CHANNEL).expect("slack_webhook channel is registered");
+    channels::test_channel(&ctx, &project, channel).await?;
 
     Ok(Json(()))
 }
@@ -3,14 +3,14 @@ use actix_web::{
     web::{self, Data, Json, Path},
     Responder,
 };
-use sea_orm::{prelude::*, ActiveValue, IntoActiveModel};
 use serde::Deserialize;
-use serde_json::json;
 use validator::Validate;
 
-use crate::entity::prelude::*;
+use super::channels::{self, EnvironmentOverride};
+use crate::notifications;
+use crate::{AppContext, Identity, Result};
 
-use crate::{AppContext, Error, Identity, Result};
+const CHANNEL: &str = "teams_webhook";
 
 pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg.service(save).service(delete).service(test);
@@ -39,135 +39,37 @@ async fn save(
     input.validate()?;
     let input = input.into_inner();
 
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
-
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
-
-    let mut project_model = project.into_active_model();
-    project_model.teams_webhook = ActiveValue::set(Some(input.webhook_url));
-    project_model.save(&ctx.db).await?;
-
-    for env_input in input.environments {
-        let Some(env) = ProjectEnvironments::find_by_id(env_input.project_environment_id)
-            .one(&ctx.db)
-            .await?
-        else {
-            continue;
-        };
-
-        let mut env = env.into_active_model();
-        env.teams_webhook = ActiveValue::set(env_input.teams_webhook);
-        env.save(&ctx.db).await?;
-    }
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
+
+    let environments = input
+        .environments
+        .into_iter()
+        .filter_map(|env| EnvironmentOverride::from_setting(env.project_environment_id, "url", env.teams_webhook))
+        .collect();
+
+    let channel = notifications::channel(CHANNEL).expect("teams_webhook channel is registered");
+    let config = serde_json::json!({ "url": input.webhook_url });
+
+    channels::save_channel(&ctx, &project, channel, config, true, Some(environments)).await?;
 
     Ok(Json(()))
 }
 
 #[post("/delete")]
 async fn delete(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
-
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
-
-    let mut project_model = project.into_active_model();
-    project_model.teams_webhook = ActiveValue::set(None);
-    project_model.save(&ctx.db).await?;
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
+
+    channels::delete_channel(&ctx, project.project_id, CHANNEL).await?;
 
     Ok(Json(()))
 }
 
 #[post("/test")]
 async fn test(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
-
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
-
-    let Some(webhook_url) = project.teams_webhook else {
-        return Err(Error::new("Teams Webhook URL is not set"));
-    };
-
-    let params = json!({
-        "type": "message",
-        "attachments": [
-            {
-                "contentType": "application/vnd.microsoft.card.adaptive",
-                "content": {
-                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
-                    "type": "AdaptiveCard",
-                    "version": "1.0",
-                    "body": [
-                        {
-                            "type": "ColumnSet",
-                            "columns": [
-                                {
-                                    "type": "Column",
-                                    "width": "auto",
-                                    "items": [
-                                        {
-                                            "type": "Image",
-                                            "url": "https://dontpanic.rs/static/favicon.png",
-                                            "size": "small",
-                                        }
-                                    ]
-                                },
-                                {
-                                    "type": "Column",
-                                    "width": "stretch",
-                                    "verticalContentAlignment": "center",
-                                    "items": [
-                                        {
-                                            "type": "TextBlock",
-                                            "text": "Don't Panic MS Teams integration is working",
-                                            "size": "medium",
-                                            "weight": "bolder",
-                                            "style": "heading",
-                                        }
-                                    ]
-                                }
-                            ]
-                        },
-                        {
-                            "type": "TextBlock",
-                            "text": format!(
-                                "You'll get notified in this channel when '{}' experiences errors or panics.",
-                                project.name
-                            ),
-                            "wrap": "true",
-                        }
-                    ]
-                }
-            }
-        ]
-    });
-
-    let client = reqwest::Client::new();
-    let res = client.post(webhook_url).json(&params).send().await?;
-
-    log::info!("MS Teams test response: {:?}", res.text().await);
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
+
+    let channel = notifications::channel(CHANNEL).expect("teams_webhook channel is registered");
+    channels::test_channel(&ctx, &project, channel).await?;
 
     Ok(Json(()))
 }
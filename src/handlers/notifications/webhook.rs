@@ -3,14 +3,14 @@ use actix_web::{
     web::{self, Data, Json, Path},
     Responder,
 };
-use sea_orm::{prelude::*, ActiveValue, IntoActiveModel};
 use serde::Deserialize;
 use validator::Validate;
 
-use crate::entity::prelude::*;
+use super::channels::{self, EnvironmentOverride};
+use crate::notifications;
+use crate::{AppContext, Identity, Result};
 
-use crate::notifications::ReportStatus;
-use crate::{AppContext, Error, Identity, Result};
+const CHANNEL: &str = "webhook";
 
 pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg.service(save).service(delete).service(test);
@@ -39,122 +39,42 @@ async fn save(
     input.validate()?;
     let input = input.into_inner();
 
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
-
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
 
-    let mut project_model = project.into_active_model();
-    project_model.webhook = ActiveValue::set(Some(input.webhook_url));
-    project_model.save(&ctx.db).await?;
+    let environments = input
+        .environments
+        .into_iter()
+        .filter_map(|env| EnvironmentOverride::from_setting(env.project_environment_id, "url", env.webhook))
+        .collect();
 
-    for env_input in input.environments {
-        let Some(env) = ProjectEnvironments::find_by_id(env_input.project_environment_id)
-            .one(&ctx.db)
-            .await?
-        else {
-            continue;
-        };
+    let channel = notifications::channel(CHANNEL).expect("webhook channel is registered");
+    // the form only edits the URL, the signing secret and headers are kept
+    let mut config = channels::channel_config(&ctx, project.project_id, CHANNEL)
+        .await?
+        .map(|(config, _)| config)
+        .unwrap_or_else(|| serde_json::json!({}));
+    config["url"] = input.webhook_url.into();
 
-        let mut env = env.into_active_model();
-        env.webhook = ActiveValue::set(env_input.webhook);
-        env.save(&ctx.db).await?;
-    }
+    channels::save_channel(&ctx, &project, channel, config, true, Some(environments)).await?;
 
     Ok(Json(()))
 }
 
 #[post("/delete")]
 async fn delete(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
 
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
-
-    let mut project_model = project.into_active_model();
-    project_model.webhook = ActiveValue::set(None);
-    project_model.save(&ctx.db).await?;
+    channels::delete_channel(&ctx, project.project_id, CHANNEL).await?;
 
     Ok(Json(()))
 }
 
 #[post("/test")]
 async fn test(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
-    let project_id = path.into_inner();
-    let project = Projects::find_by_id(project_id)
-        .one(&ctx.db)
-        .await?
-        .ok_or(Error::NotFound)?;
+    let project = channels::find_project(&ctx, &id, path.into_inner()).await?;
 
-    let user = id.user(&ctx).await?;
-    let _ = user
-        .role(&ctx.db, project.organization_id)
-        .await?
-        .ok_or(Error::LoginRequired)?;
-
-    let Some(webhook_url) = project.webhook else {
-        return Err(Error::new("Webhook URL is not set"));
-    };
-
-    let example_log = serde_json::json!([{
-        "timestamp": chrono::Utc::now().timestamp() as u64,
-        "level": 1,
-        "message": "Error message",
-        "module": "my_module",
-        "file": "src/main.rs",
-        "line": 42,
-    }]);
-
-    let params = serde_json::json!({
-        "status": ReportStatus::New,
-        "title": "Called `Option::unwrap()` on a `None` value (Webhook Test)",
-        "project": project.name,
-        "environment": "development",
-        "backtrace": EXAMPLE_BACKTRACE,
-        "log": serde_json::to_string(&example_log).unwrap(),
-        "url": "https://dontpanic.rs",
-    });
-
-    let client = reqwest::Client::new();
-    let _res = client.post(webhook_url).json(&params).send().await?;
+    let channel = notifications::channel(CHANNEL).expect("webhook channel is registered");
+    channels::test_channel(&ctx, &project, channel).await?;
 
     Ok(Json(()))
 }
-
-const EXAMPLE_BACKTRACE: &str = r#"stack backtrace:
-0: playground::main::h6849180917e9510b (0x55baf1676201)
-            at src/main.rs:4
-1: std::rt::lang_start::{{closure}}::hb3ceb20351fe39ee (0x55baf1675faf)
-            at /rustc/3c235d5600393dfe6c36eeed34042efad8d4f26e/src/libstd/rt.rs:64
-2: {{closure}} (0x55baf16be492)
-            at src/libstd/rt.rs:49
-    do_call<closure,i32>
-            at src/libstd/panicking.rs:293
-3: __rust_maybe_catch_panic (0x55baf16c00b9)
-            at src/libpanic_unwind/lib.rs:87
-4: try<i32,closure> (0x55baf16bef9c)
-            at src/libstd/panicking.rs:272
-    catch_unwind<closure,i32>
-            at src/libstd/panic.rs:388
-    lang_start_internal
-            at src/libstd/rt.rs:48
-5: std::rt::lang_start::h2c4217f9057b6ddb (0x55baf1675f88)
-            at /rustc/3c235d5600393dfe6c36eeed34042efad8d4f26e/src/libstd/rt.rs:64
-6: main (0x55baf16762f9)
-7: __libc_start_main (0x7fab051b9b96)
-8: _start (0x55baf1675e59)
-9: <unknown> (0x0)"#;
@@ -20,6 +20,12 @@ use crate::{AppContext, Error, Identity, Result};
 mod projects;
 use projects::OrganizationProject;
 
+mod environments;
+mod grouping_rules;
+mod inbound_filters;
+mod keys;
+mod releases;
+
 mod members;
 use members::OrganizationMember;
 
@@ -28,6 +34,15 @@ mod stats;
 pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg.service(list)
         .service(create)
+        .service(web::scope("/{organization_id}/projects/{project_id}/environments").configure(environments::routes))
+        .service(
+            web::scope("/{organization_id}/projects/{project_id}/grouping-rules").configure(grouping_rules::routes),
+        )
+        .service(
+            web::scope("/{organization_id}/projects/{project_id}/inbound-filters").configure(inbound_filters::routes),
+        )
+        .service(web::scope("/{organization_id}/projects/{project_id}/keys").configure(keys::routes))
+        .service(web::scope("/{organization_id}/projects/{project_id}/releases").configure(releases::routes))
         .service(web::scope("/{organization_id}/projects").configure(projects::routes))
         .service(web::scope("/{organization_id}/members").configure(members::routes))
         .service(web::scope("/{organization_id}/stats").configure(stats::routes))
@@ -35,6 +50,33 @@ pub fn routes(cfg: &mut web::ServiceConfig) {
         .service(edit);
 }
 
+/// Finds a project of an organization the user is a member of,
+/// changes to the project settings are limited to admins and owners with `require_admin`
+pub(crate) async fn find_project(
+    ctx: &AppContext<'_>,
+    id: &Identity,
+    organization_id: u32,
+    project_id: u32,
+    require_admin: bool,
+) -> Result<crate::entity::projects::Model> {
+    let user = id.user(ctx).await?;
+    let user_role = user.role(&ctx.db, organization_id).await?.ok_or(Error::LoginRequired)?;
+
+    if require_admin && user_role != "admin" && user_role != "owner" {
+        return Err(Error::new(
+            "You do not have permission to change the settings of this project",
+        ));
+    }
+
+    let project = Projects::find_by_id(project_id)
+        .filter(crate::entity::projects::Column::OrganizationId.eq(organization_id))
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(project)
+}
+
 #[derive(Serialize, Debug)]
 struct Organization {
     organization_id: u32,
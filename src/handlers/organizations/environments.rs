@@ -0,0 +1,596 @@
+use actix_web::{
+    get, post, web,
+    web::{Data, Json, Path, Query},
+    Responder,
+};
+use chrono::prelude::*;
+use sea_orm::{
+    prelude::*, ActiveValue, Condition, DatabaseTransaction, IntoActiveModel, QueryOrder, QueryTrait, TransactionTrait,
+    TryIntoModel,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::entity::prelude::*;
+use crate::entity::project_environments;
+use crate::entity::project_keys;
+use crate::entity::project_report_merges;
+use crate::entity::project_reports;
+use crate::entity::projects;
+
+use super::find_project;
+use super::keys::replace_revoked_main_key;
+use crate::{AppContext, Error, Identity, Result};
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list)
+        .service(create)
+        .service(merge)
+        .service(delete)
+        .service(update);
+}
+
+#[derive(Debug, Deserialize)]
+struct ListQuery {
+    /// Hidden environments are left out unless this is set to 1
+    hidden: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct CreateInput {
+    #[validate(length(min = 1, max = 80, message = "Name must be between 1 and 80 characters"))]
+    name: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct UpdateInput {
+    #[validate(length(min = 1, max = 80, message = "Name must be between 1 and 80 characters"))]
+    name: Option<String>,
+    is_hidden: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeInput {
+    /// Environment that receives the reports and future events of the merged one
+    into: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeleteInput {
+    /// Environment the reports are moved to, they are deleted when missing
+    move_to: Option<u32>,
+}
+
+/// Environments that were merged into another one can't be changed anymore
+async fn find_environment(
+    ctx: &AppContext<'_>,
+    project: &projects::Model,
+    environment_id: u32,
+) -> Result<Option<project_environments::Model>> {
+    let environment = project
+        .find_related(ProjectEnvironments)
+        .filter(project_environments::Column::ProjectEnvironmentId.eq(environment_id))
+        .filter(project_environments::Column::MergedIntoId.is_null())
+        .one(&ctx.db)
+        .await?;
+
+    Ok(environment)
+}
+
+async fn ensure_unique_name(
+    ctx: &AppContext<'_>,
+    project: &projects::Model,
+    name: &str,
+    except_id: Option<u32>,
+) -> Result<()> {
+    let existing = project
+        .find_related(ProjectEnvironments)
+        .filter(
+            Condition::any()
+                .add(project_environments::Column::Name.eq(name))
+                .add(project_environments::Column::GroupingName.eq(name)),
+        )
+        .apply_if(except_id, |query, v| {
+            query.filter(project_environments::Column::ProjectEnvironmentId.ne(v))
+        })
+        .one(&ctx.db)
+        .await?;
+
+    if existing.is_some() {
+        return Err(Error::field(
+            "name",
+            "An environment with this name already exists".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Assigns everything attached to an environment to another one
+async fn move_environment(
+    txn: &DatabaseTransaction,
+    project: &projects::Model,
+    from_id: u32,
+    to_id: u32,
+) -> Result<()> {
+    ProjectReports::update_many()
+        .col_expr(project_reports::Column::ProjectEnvironmentId, Expr::value(to_id))
+        .filter(project_reports::Column::ProjectEnvironmentId.eq(from_id))
+        .exec(txn)
+        .await?;
+
+    ProjectReportMerges::update_many()
+        .col_expr(project_report_merges::Column::ProjectEnvironmentId, Expr::value(to_id))
+        .filter(project_report_merges::Column::ProjectEnvironmentId.eq(from_id))
+        .exec(txn)
+        .await?;
+
+    ProjectKeys::update_many()
+        .col_expr(project_keys::Column::ProjectEnvironmentId, Expr::value(to_id))
+        .filter(project_keys::Column::ProjectEnvironmentId.eq(from_id))
+        .exec(txn)
+        .await?;
+
+    ProjectEnvironments::update_many()
+        .col_expr(project_environments::Column::MergedIntoId, Expr::value(to_id))
+        .filter(project_environments::Column::MergedIntoId.eq(from_id))
+        .exec(txn)
+        .await?;
+
+    if project.default_environment_id == Some(from_id) {
+        Projects::update_many()
+            .col_expr(projects::Column::DefaultEnvironmentId, Expr::value(to_id))
+            .filter(projects::Column::ProjectId.eq(project.project_id))
+            .exec(txn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[get("")]
+async fn list(
+    ctx: Data<AppContext<'_>>,
+    path: Path<(u32, u32)>,
+    id: Identity,
+    q: Query<ListQuery>,
+) -> Result<impl Responder> {
+    let (organization_id, project_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, false).await?;
+    let hidden = q.hidden.unwrap_or_default();
+
+    let environments = project
+        .find_related(ProjectEnvironments)
+        .filter(project_environments::Column::MergedIntoId.is_null())
+        .apply_if((hidden == 0).then_some(0), |query, v| {
+            query.filter(project_environments::Column::IsHidden.eq(v))
+        })
+        .order_by_asc(project_environments::Column::Name)
+        .all(&ctx.db)
+        .await?;
+
+    Ok(Json(environments))
+}
+
+#[post("")]
+async fn create(
+    ctx: Data<AppContext<'_>>,
+    path: Path<(u32, u32)>,
+    id: Identity,
+    input: Json<CreateInput>,
+) -> Result<impl Responder> {
+    input.validate()?;
+
+    let (organization_id, project_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, true).await?;
+    ensure_unique_name(&ctx, &project, &input.name, None).await?;
+
+    let environment = project_environments::ActiveModel {
+        project_id: ActiveValue::set(project.project_id),
+        name: ActiveValue::set(input.into_inner().name),
+        ..Default::default()
+    };
+
+    let environment = environment.insert(&ctx.db).await?;
+
+    Ok(Json(environment))
+}
+
+/// Renames or hides an environment. Renamed environments keep grouping events into their existing reports.
+#[post("/{environment_id}")]
+async fn update(
+    ctx: Data<AppContext<'_>>,
+    path: Path<(u32, u32, u32)>,
+    id: Identity,
+    input: Json<UpdateInput>,
+) -> Result<impl Responder> {
+    input.validate()?;
+
+    let (organization_id, project_id, environment_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, true).await?;
+    let environment = find_environment(&ctx, &project, environment_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let input = input.into_inner();
+    let mut row = environment.clone().into_active_model();
+
+    if let Some(name) = input.name.filter(|name| *name != environment.name) {
+        ensure_unique_name(&ctx, &project, &name, Some(environment.project_environment_id)).await?;
+
+        // report uids are computed with the name events were first sent with
+        if environment.grouping_name.is_none() {
+            row.grouping_name = ActiveValue::set(Some(environment.name.clone()));
+        }
+
+        row.name = ActiveValue::set(name);
+    }
+
+    if let Some(is_hidden) = input.is_hidden {
+        row.is_hidden = ActiveValue::set(is_hidden.into());
+    }
+
+    let environment = row.save(&ctx.db).await?.try_into_model()?;
+
+    Ok(Json(environment))
+}
+
+/// Moves the reports of an environment to another one. Events still sent to the merged
+/// environment are assigned to the one it was merged into.
+#[post("/{environment_id}/merge")]
+async fn merge(
+    ctx: Data<AppContext<'_>>,
+    path: Path<(u32, u32, u32)>,
+    id: Identity,
+    input: Json<MergeInput>,
+) -> Result<impl Responder> {
+    let (organization_id, project_id, environment_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, true).await?;
+    let environment = find_environment(&ctx, &project, environment_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if input.into == environment.project_environment_id {
+        return Err(Error::field(
+            "into",
+            "An environment can't be merged into itself".into(),
+        ));
+    }
+
+    let target = find_environment(&ctx, &project, input.into)
+        .await?
+        .ok_or_else(|| Error::field("into", "Environment not found".into()))?;
+
+    let txn = ctx.db.begin().await?;
+
+    move_environment(
+        &txn,
+        &project,
+        environment.project_environment_id,
+        target.project_environment_id,
+    )
+    .await?;
+
+    let mut row = environment.into_active_model();
+    row.merged_into_id = ActiveValue::set(Some(target.project_environment_id));
+    row.save(&txn).await?;
+
+    txn.commit().await?;
+
+    Ok(Json(target))
+}
+
+/// Deletes an environment with its reports, or moves the reports to another environment first
+#[post("/{environment_id}/delete")]
+async fn delete(
+    ctx: Data<AppContext<'_>>,
+    path: Path<(u32, u32, u32)>,
+    id: Identity,
+    input: Json<DeleteInput>,
+) -> Result<impl Responder> {
+    let (organization_id, project_id, environment_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, true).await?;
+    let environment = find_environment(&ctx, &project, environment_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let move_to = match input.move_to {
+        Some(move_to) if move_to == environment.project_environment_id => {
+            return Err(Error::field(
+                "move_to",
+                "Reports can't be moved to the deleted environment".into(),
+            ));
+        }
+        Some(move_to) => Some(
+            find_environment(&ctx, &project, move_to)
+                .await?
+                .ok_or_else(|| Error::field("move_to", "Environment not found".into()))?,
+        ),
+        None => None,
+    };
+
+    let txn = ctx.db.begin().await?;
+
+    let deleted_reports = match move_to {
+        Some(target) => {
+            move_environment(
+                &txn,
+                &project,
+                environment.project_environment_id,
+                target.project_environment_id,
+            )
+            .await?;
+
+            0
+        }
+        None => {
+            // environments merged into this one go away with it
+            let environment_ids: Vec<u32> = project
+                .find_related(ProjectEnvironments)
+                .filter(project_environments::Column::MergedIntoId.eq(environment.project_environment_id))
+                .all(&txn)
+                .await?
+                .into_iter()
+                .map(|merged| merged.project_environment_id)
+                .chain([environment.project_environment_id])
+                .collect();
+
+            let res = ProjectReports::delete_many()
+                .filter(project_reports::Column::ProjectEnvironmentId.is_in(environment_ids.clone()))
+                .exec(&txn)
+                .await?;
+
+            ProjectReportMerges::update_many()
+                .col_expr(
+                    project_report_merges::Column::ProjectEnvironmentId,
+                    Expr::value(Option::<u32>::None),
+                )
+                .filter(project_report_merges::Column::ProjectEnvironmentId.is_in(environment_ids.clone()))
+                .exec(&txn)
+                .await?;
+
+            // keys restricted to the environment would otherwise be able to send events anywhere
+            ProjectKeys::update_many()
+                .col_expr(project_keys::Column::Revoked, Expr::value(Utc::now().naive_utc()))
+                .filter(project_keys::Column::ProjectEnvironmentId.is_in(environment_ids.clone()))
+                .filter(project_keys::Column::Revoked.is_null())
+                .exec(&txn)
+                .await?;
+
+            replace_revoked_main_key(&txn, &project).await?;
+
+            if project
+                .default_environment_id
+                .is_some_and(|default_id| environment_ids.contains(&default_id))
+            {
+                let mut project_row = project.clone().into_active_model();
+                project_row.default_environment_id = ActiveValue::set(None);
+                project_row.save(&txn).await?;
+            }
+
+            ProjectEnvironments::delete_many()
+                .filter(project_environments::Column::MergedIntoId.eq(environment.project_environment_id))
+                .exec(&txn)
+                .await?;
+
+            res.rows_affected
+        }
+    };
+
+    environment.delete(&txn).await?;
+
+    txn.commit().await?;
+
+    Ok(Json(json!({
+        "deleted_reports": deleted_reports,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test;
+    use serde_json::Value;
+
+    #[actix_web::test]
+    async fn test_environments() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Environments Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        let environments_uri = format!("/api/organizations/1/projects/{}/environments", project_id);
+
+        let event = |title: &str, env: &str| {
+            serde_json::json!({
+                "key": api_key,
+                "env": env,
+                "data": {
+                    "title": title,
+                    "trace": "",
+                    "log": [],
+                    "os": "linux",
+                    "arch": "x86_64",
+                    "ver": "1.0.0"
+                }
+            })
+        };
+
+        let req = test::TestRequest::post()
+            .uri("/ingress/batch")
+            .set_json(vec![event("Disk full", "prod"), event("Disk full", "staging")])
+            .to_request();
+
+        test::call_service(&app, req).await;
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&environments_uri)
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let environments = res.as_array().unwrap();
+        assert_eq!(environments.len(), 2);
+        let prod_id = environments[0]["project_environment_id"].as_u64().unwrap();
+        let staging_id = environments[1]["project_environment_id"].as_u64().unwrap();
+
+        let reports = |query: String| {
+            let req = test::TestRequest::get()
+                .uri(&format!("/api/reports?project_id={}{}", project_id, query))
+                .cookie(sess.clone())
+                .to_request();
+
+            test::call_service(&app, req)
+        };
+
+        // names are unique within a project
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}", environments_uri, staging_id))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "prod" }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        // renamed environments keep grouping events into their reports
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}", environments_uri, prod_id))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "production" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["name"], "production");
+
+        let req = test::TestRequest::post()
+            .uri("/ingress")
+            .set_json(event("Disk full", "production"))
+            .to_request();
+
+        test::call_service(&app, req).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::read_body_json(reports(format!("&environment_id={}", prod_id)).await).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 1);
+        assert_eq!(res["reports"][0]["env"]["name"], "production");
+
+        // clients still sending the old name don't create a new environment with the same reports
+        let req = test::TestRequest::post()
+            .uri(&environments_uri)
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "prod" }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        let req = test::TestRequest::post()
+            .uri("/ingress")
+            .set_json(event("Disk full", "prod"))
+            .to_request();
+
+        test::call_service(&app, req).await;
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&environments_uri)
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res.as_array().unwrap().len(), 2);
+
+        let res: Value = test::read_body_json(reports(format!("&environment_id={}", prod_id)).await).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 1);
+
+        let res: Value = test::read_body_json(reports(format!("&environment_id={}", staging_id)).await).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 1);
+
+        // reports in hidden environments are left out of the list
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}", environments_uri, staging_id))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "is_hidden": true }))
+            .to_request();
+
+        test::call_service(&app, req).await;
+
+        let res: Value = test::read_body_json(reports(String::new()).await).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 1);
+
+        let res: Value = test::read_body_json(reports(format!("&environment_id={}", staging_id)).await).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 1);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("{}?hidden=1", environments_uri))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res.as_array().unwrap().len(), 2);
+
+        // merged reports and events move to the target environment
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}/merge", environments_uri, staging_id))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "into": prod_id }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+
+        let req = test::TestRequest::post()
+            .uri("/ingress")
+            .set_json(event("Index out of bounds", "staging"))
+            .to_request();
+
+        test::call_service(&app, req).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::read_body_json(reports(format!("&environment_id={}", prod_id)).await).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 3);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("{}?hidden=1", environments_uri))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res.as_array().unwrap().len(), 1);
+
+        // deleting an environment deletes its reports
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}/delete", environments_uri, prod_id))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({}))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["deleted_reports"], 3);
+
+        let res: Value = test::read_body_json(reports(String::new()).await).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 0);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("{}?hidden=1", environments_uri))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res.as_array().unwrap().len(), 0);
+    }
+}
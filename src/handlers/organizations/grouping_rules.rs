@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use actix_web::{
+    get, post, web,
+    web::{Data, Json, Path},
+    Responder,
+};
+use sea_orm::{prelude::*, sea_query::Query, ActiveValue, QueryOrder, QuerySelect, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::entity::prelude::*;
+use crate::entity::project_grouping_rules;
+use crate::entity::project_report_events;
+use crate::entity::project_reports;
+
+use super::find_project;
+use crate::backtrace::Frame;
+use crate::grouping::{self, EventFingerprint, GroupingRule, GroupingStrategy};
+use crate::{AppContext, Error, Identity, Result};
+
+// the preview only regroups the most recently seen reports
+const PREVIEW_REPORTS: u64 = 500;
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list).service(save).service(preview);
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+struct RuleInput {
+    field: String,
+    action: String,
+    #[validate(length(min = 1, max = 255, message = "Pattern must be between 1 and 255 characters"))]
+    pattern: String,
+    #[validate(length(max = 255, message = "Replacement must be at most 255 characters"))]
+    replacement: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct RulesInput {
+    #[validate(length(max = 50, message = "A project can have at most 50 grouping rules"), nested)]
+    rules: Vec<RuleInput>,
+}
+
+impl RulesInput {
+    fn compile(&self) -> Result<Vec<GroupingRule>> {
+        self.rules
+            .iter()
+            .enumerate()
+            .map(|(idx, rule)| {
+                GroupingRule::new(&rule.field, &rule.action, &rule.pattern, rule.replacement.as_deref())
+                    .map_err(|e| Error::field("rules", format!("Rule {}: {}", idx + 1, e).into()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct ReportRef {
+    project_report_id: u32,
+    title: String,
+}
+
+#[derive(Serialize, Debug)]
+struct Preview {
+    checked: usize,
+    /// Reports whose next events would be grouped differently
+    changed: Vec<ReportRef>,
+    /// Existing reports that would receive events under the same uid
+    merged: Vec<Vec<ReportRef>>,
+}
+
+#[get("")]
+async fn list(ctx: Data<AppContext<'_>>, path: Path<(u32, u32)>, id: Identity) -> Result<impl Responder> {
+    let (organization_id, project_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, false).await?;
+
+    let rules = project
+        .find_related(ProjectGroupingRules)
+        .order_by_asc(project_grouping_rules::Column::Position)
+        .all(&ctx.db)
+        .await?;
+
+    Ok(Json(rules))
+}
+
+/// Replaces all grouping rules of a project, rules are applied in the given order
+#[post("")]
+async fn save(
+    ctx: Data<AppContext<'_>>,
+    path: Path<(u32, u32)>,
+    id: Identity,
+    input: Json<RulesInput>,
+) -> Result<impl Responder> {
+    input.validate()?;
+    input.compile()?;
+
+    let (organization_id, project_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, true).await?;
+
+    let txn = ctx.db.begin().await?;
+
+    ProjectGroupingRules::delete_many()
+        .filter(project_grouping_rules::Column::ProjectId.eq(project.project_id))
+        .exec(&txn)
+        .await?;
+
+    for (position, rule) in input.into_inner().rules.into_iter().enumerate() {
+        let row = project_grouping_rules::ActiveModel {
+            project_id: ActiveValue::set(project.project_id),
+            position: ActiveValue::set(position as u32),
+            field: ActiveValue::set(rule.field),
+            action: ActiveValue::set(rule.action),
+            pattern: ActiveValue::set(rule.pattern),
+            replacement: ActiveValue::set(rule.replacement),
+            ..Default::default()
+        };
+
+        row.insert(&txn).await?;
+    }
+
+    txn.commit().await?;
+
+    let rules = project
+        .find_related(ProjectGroupingRules)
+        .order_by_asc(project_grouping_rules::Column::Position)
+        .all(&ctx.db)
+        .await?;
+
+    Ok(Json(rules))
+}
+
+/// Shows how the project's recent reports would be grouped with the given rules, without saving them.
+/// Client supplied fingerprints aren't stored, so reports grouped by one are previewed as if they had none.
+#[post("/preview")]
+async fn preview(
+    ctx: Data<AppContext<'_>>,
+    path: Path<(u32, u32)>,
+    id: Identity,
+    input: Json<RulesInput>,
+) -> Result<impl Responder> {
+    input.validate()?;
+    let proposed_rules = input.compile()?;
+
+    let (organization_id, project_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, true).await?;
+
+    let current_rules = grouping::project_rules(&ctx.db, project.project_id).await?;
+    let strategy = project.grouping.parse::<GroupingStrategy>().unwrap_or_default();
+
+    let environments: HashMap<u32, String> = project
+        .find_related(ProjectEnvironments)
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .map(|env| (env.project_environment_id, env.grouping_name.unwrap_or(env.name)))
+        .collect();
+
+    let reports = project
+        .find_related(ProjectReports)
+        .order_by_desc(project_reports::Column::LastSeen)
+        .limit(PREVIEW_REPORTS)
+        .all(&ctx.db)
+        .await?;
+
+    // frames of the latest event of every report
+    let latest_events = Query::select()
+        .expr(Expr::col(project_report_events::Column::ProjectReportEventId).max())
+        .from(ProjectReportEvents)
+        .and_where(project_report_events::Column::ProjectReportId.is_in(reports.iter().map(|r| r.project_report_id)))
+        .group_by_col(project_report_events::Column::ProjectReportId)
+        .to_owned();
+
+    let frames: HashMap<u32, Vec<Frame>> = ProjectReportEvents::find()
+        .select_only()
+        .columns([
+            project_report_events::Column::ProjectReportId,
+            project_report_events::Column::Frames,
+        ])
+        .filter(project_report_events::Column::ProjectReportEventId.in_subquery(latest_events))
+        .into_tuple::<(u32, Option<String>)>()
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .map(|(report_id, frames)| {
+            let frames = frames.and_then(|f| serde_json::from_str(&f).ok()).unwrap_or_default();
+            (report_id, frames)
+        })
+        .collect();
+
+    let mut changed = vec![];
+    let mut groups: Vec<(String, Vec<ReportRef>)> = vec![];
+
+    for report in &reports {
+        let environment = report
+            .project_environment_id
+            .and_then(|env_id| environments.get(&env_id))
+            .map(|name| name.as_str())
+            .unwrap_or_default();
+
+        // titles are stored as "{title} in {file}:{line}"
+        let location = report
+            .title
+            .rsplit_once(" in ")
+            .map(|(_, location)| location)
+            .filter(|location| *location != "Unknown");
+
+        let fingerprint = |rules| EventFingerprint {
+            project_id: project.project_id,
+            environment_hash: grouping::environment_hash(environment),
+            title: &report.title,
+            location,
+            frames: frames
+                .get(&report.project_report_id)
+                .map(|f| f.as_slice())
+                .unwrap_or_default(),
+            custom: None,
+            rules,
+        };
+
+        let current_uid = fingerprint(&current_rules).report_uid(strategy);
+        let proposed_uid = fingerprint(&proposed_rules).report_uid(strategy);
+
+        let report_ref = || ReportRef {
+            project_report_id: report.project_report_id,
+            title: report.title.clone(),
+        };
+
+        if current_uid != proposed_uid {
+            changed.push(report_ref());
+        }
+
+        match groups.iter_mut().find(|(uid, _)| *uid == proposed_uid) {
+            Some((_, group)) => group.push(report_ref()),
+            None => groups.push((proposed_uid, vec![report_ref()])),
+        }
+    }
+
+    let merged = groups
+        .into_iter()
+        .map(|(_, group)| group)
+        .filter(|group| group.len() > 1)
+        .collect();
+
+    Ok(Json(Preview {
+        checked: reports.len(),
+        changed,
+        merged,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test;
+    use serde_json::Value;
+
+    #[actix_web::test]
+    async fn test_grouping_rules() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Rules Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        let ingest = |titles: &[&str]| {
+            let events: Vec<Value> = titles
+                .iter()
+                .map(|title| {
+                    serde_json::json!({
+                        "key": api_key,
+                        "data": {
+                            "title": title,
+                            "trace": "",
+                            "log": [],
+                            "os": "linux",
+                            "arch": "x86_64"
+                        }
+                    })
+                })
+                .collect();
+
+            test::TestRequest::post()
+                .uri("/ingress/batch")
+                .set_json(events)
+                .to_request()
+        };
+
+        test::call_service(&app, ingest(&["Tenant acme failed", "Tenant globex failed", "Timeout"])).await;
+        crate::process_queues(&ctx).await;
+
+        let rules_uri = format!("/api/organizations/1/projects/{}/grouping-rules", project_id);
+        let rules = serde_json::json!({
+            "rules": [
+                { "field": "title", "action": "replace", "pattern": "Tenant \\w+", "replacement": "Tenant <name>" },
+                { "field": "title", "action": "group", "pattern": "^Timeout", "replacement": "timeouts" },
+            ]
+        });
+
+        // invalid patterns are rejected
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/preview", rules_uri))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "rules": [{ "field": "title", "action": "replace", "pattern": "(" }] }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        // preview against existing reports
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/preview", rules_uri))
+            .cookie(sess.clone())
+            .set_json(&rules)
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["checked"], 3);
+        assert_eq!(res["changed"].as_array().unwrap().len(), 3);
+        assert_eq!(res["merged"].as_array().unwrap().len(), 1);
+        assert_eq!(res["merged"][0].as_array().unwrap().len(), 2);
+
+        // save
+        let req = test::TestRequest::post()
+            .uri(&rules_uri)
+            .cookie(sess.clone())
+            .set_json(&rules)
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res.as_array().unwrap().len(), 2);
+        assert_eq!(res[1]["action"], "group");
+
+        // new events are grouped with the saved rules
+        test::call_service(
+            &app,
+            ingest(&["Tenant initech failed", "Tenant umbrella failed", "Timeout after 5s"]),
+        )
+        .await;
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports?project_id={}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 5);
+
+        // rules are listed in order
+        let req = test::TestRequest::get()
+            .uri(&rules_uri)
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res[0]["pattern"], "Tenant \\w+");
+    }
+}
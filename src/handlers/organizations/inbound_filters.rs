@@ -0,0 +1,195 @@
+use actix_web::{
+    get, post, web,
+    web::{Data, Json, Path},
+    Responder,
+};
+use sea_orm::{prelude::*, ActiveValue, QueryOrder, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::entity::prelude::*;
+use crate::entity::project_inbound_filters;
+
+use super::find_project;
+use crate::filters::InboundFilter;
+use crate::{AppContext, Error, Identity, Result};
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list).service(save);
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+struct FilterInput {
+    field: String,
+    #[validate(length(min = 1, max = 255, message = "Pattern must be between 1 and 255 characters"))]
+    pattern: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct FiltersInput {
+    #[validate(length(max = 50, message = "A project can have at most 50 inbound filters"), nested)]
+    filters: Vec<FilterInput>,
+}
+
+#[get("")]
+async fn list(ctx: Data<AppContext<'_>>, path: Path<(u32, u32)>, id: Identity) -> Result<impl Responder> {
+    let (organization_id, project_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, false).await?;
+
+    let filters = project
+        .find_related(ProjectInboundFilters)
+        .order_by_asc(project_inbound_filters::Column::ProjectInboundFilterId)
+        .all(&ctx.db)
+        .await?;
+
+    Ok(Json(filters))
+}
+
+/// Replaces all inbound filters of a project
+#[post("")]
+async fn save(
+    ctx: Data<AppContext<'_>>,
+    path: Path<(u32, u32)>,
+    id: Identity,
+    input: Json<FiltersInput>,
+) -> Result<impl Responder> {
+    input.validate()?;
+
+    for (idx, filter) in input.filters.iter().enumerate() {
+        InboundFilter::new(&filter.field, &filter.pattern)
+            .map_err(|e| Error::field("filters", format!("Filter {}: {}", idx + 1, e).into()))?;
+    }
+
+    let (organization_id, project_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, true).await?;
+
+    let txn = ctx.db.begin().await?;
+
+    ProjectInboundFilters::delete_many()
+        .filter(project_inbound_filters::Column::ProjectId.eq(project.project_id))
+        .exec(&txn)
+        .await?;
+
+    for filter in input.into_inner().filters {
+        let row = project_inbound_filters::ActiveModel {
+            project_id: ActiveValue::set(project.project_id),
+            field: ActiveValue::set(filter.field),
+            pattern: ActiveValue::set(filter.pattern),
+            ..Default::default()
+        };
+
+        row.insert(&txn).await?;
+    }
+
+    txn.commit().await?;
+
+    let filters = project
+        .find_related(ProjectInboundFilters)
+        .order_by_asc(project_inbound_filters::Column::ProjectInboundFilterId)
+        .all(&ctx.db)
+        .await?;
+
+    Ok(Json(filters))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test;
+    use serde_json::Value;
+
+    #[actix_web::test]
+    async fn test_inbound_filters() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Filters Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        let filters_uri = format!("/api/organizations/1/projects/{}/inbound-filters", project_id);
+
+        // invalid version ranges are rejected
+        let req = test::TestRequest::post()
+            .uri(&filters_uri)
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "filters": [{ "field": "version", "pattern": "latest" }] }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        let req = test::TestRequest::post()
+            .uri(&filters_uri)
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({
+                "filters": [
+                    { "field": "os", "pattern": "windows" },
+                    { "field": "title", "pattern": "^Bot " },
+                    { "field": "version", "pattern": "<1.0.0" },
+                ]
+            }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res.as_array().unwrap().len(), 3);
+
+        let events: Vec<Value> = [
+            ("Disk full", "windows", "1.0.0"),
+            ("Bot request", "linux", "1.0.0"),
+            ("Index out of bounds", "linux", "0.9.0"),
+            ("Index out of bounds", "linux", "1.0.0"),
+        ]
+        .iter()
+        .map(|(title, os, version)| {
+            serde_json::json!({
+                "key": api_key,
+                "data": {
+                    "title": title,
+                    "trace": "",
+                    "log": [],
+                    "os": os,
+                    "arch": "x86_64",
+                    "ver": version
+                }
+            })
+        })
+        .collect();
+
+        let req = test::TestRequest::post()
+            .uri("/ingress/batch")
+            .set_json(events)
+            .to_request();
+
+        test::call_service(&app, req).await;
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports?project_id={}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let reports = res["reports"].as_array().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0]["report"]["last_version"], "1.0.0");
+
+        // dropped events are counted per filter field
+        let req = test::TestRequest::get()
+            .uri("/api/organizations/1/stats?grouping=daily&category=dropped")
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let today = res["dataset"].as_array().unwrap().last().unwrap().clone();
+        assert_eq!(today["os"], 1);
+        assert_eq!(today["title"], 1);
+        assert_eq!(today["version"], 1);
+    }
+}
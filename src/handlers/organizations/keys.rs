@@ -0,0 +1,474 @@
+use actix_web::{
+    get, post, web,
+    web::{Data, Json, Path},
+    Responder,
+};
+use chrono::prelude::*;
+use rand::{distr::Alphanumeric, prelude::*};
+use sea_orm::{prelude::*, ActiveValue, IntoActiveModel, QueryOrder, TransactionTrait, TryIntoModel};
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::entity::prelude::*;
+use crate::entity::project_environments;
+use crate::entity::project_keys;
+use crate::entity::projects;
+
+use super::find_project;
+use crate::{AppContext, Error, Identity, Result};
+
+// longest time a rotated key keeps working next to its replacement
+const MAX_GRACE_MINUTES: u32 = 7 * 24 * 60;
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list).service(create).service(rotate).service(revoke);
+}
+
+pub(super) fn generate_key() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+/// Points the project's main key to the newest key still active, once its current one is revoked.
+/// Without any active key left it's replaced with a key that isn't accepted anywhere.
+pub(super) async fn replace_revoked_main_key(db: &impl ConnectionTrait, project: &projects::Model) -> Result<()> {
+    let main_key_active = project
+        .find_related(ProjectKeys)
+        .filter(project_keys::Column::Key.eq(&project.api_key))
+        .filter(project_keys::Column::Revoked.is_null())
+        .one(db)
+        .await?
+        .is_some();
+
+    if main_key_active {
+        return Ok(());
+    }
+
+    let replacement = project
+        .find_related(ProjectKeys)
+        .filter(project_keys::Column::Revoked.is_null())
+        .order_by_desc(project_keys::Column::ProjectKeyId)
+        .one(db)
+        .await?;
+
+    let mut project = project.clone().into_active_model();
+    project.api_key = ActiveValue::set(replacement.map_or_else(generate_key, |key| key.key));
+    project.save(db).await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct KeyInput {
+    #[validate(length(min = 1, max = 80, message = "Label must be between 1 and 80 characters"))]
+    label: String,
+    expires: Option<NaiveDateTime>,
+    /// Restricts the key to sending events to this environment
+    project_environment_id: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RotateInput {
+    /// Minutes the old key keeps working, it's revoked right away when missing
+    grace_minutes: Option<u32>,
+}
+
+async fn find_active_key(ctx: &AppContext<'_>, project: &projects::Model, key_id: u32) -> Result<project_keys::Model> {
+    let key = project
+        .find_related(ProjectKeys)
+        .filter(project_keys::Column::ProjectKeyId.eq(key_id))
+        .filter(project_keys::Column::Revoked.is_null())
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(key)
+}
+
+#[get("")]
+async fn list(ctx: Data<AppContext<'_>>, path: Path<(u32, u32)>, id: Identity) -> Result<impl Responder> {
+    let (organization_id, project_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, false).await?;
+
+    let keys = project
+        .find_related(ProjectKeys)
+        .order_by_asc(project_keys::Column::ProjectKeyId)
+        .all(&ctx.db)
+        .await?;
+
+    Ok(Json(keys))
+}
+
+#[post("")]
+async fn create(
+    ctx: Data<AppContext<'_>>,
+    path: Path<(u32, u32)>,
+    id: Identity,
+    input: Json<KeyInput>,
+) -> Result<impl Responder> {
+    input.validate()?;
+    let input = input.into_inner();
+
+    if input.expires.is_some_and(|expires| expires <= Utc::now().naive_utc()) {
+        return Err(Error::field("expires", "Expiry must be in the future".into()));
+    }
+
+    let (organization_id, project_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, true).await?;
+
+    if let Some(project_environment_id) = input.project_environment_id {
+        project
+            .find_related(ProjectEnvironments)
+            .filter(project_environments::Column::ProjectEnvironmentId.eq(project_environment_id))
+            .one(&ctx.db)
+            .await?
+            .ok_or_else(|| Error::field("project_environment_id", "Environment not found".into()))?;
+    }
+
+    let key = project_keys::ActiveModel {
+        project_id: ActiveValue::set(project.project_id),
+        key: ActiveValue::set(generate_key()),
+        label: ActiveValue::set(input.label),
+        expires: ActiveValue::set(input.expires),
+        project_environment_id: ActiveValue::set(input.project_environment_id),
+        ..Default::default()
+    };
+
+    let key = key.insert(&ctx.db).await?;
+
+    Ok(Json(key))
+}
+
+/// Replaces a key with a new one with the same label, the old key can keep working for a grace period
+#[post("/{key_id}/rotate")]
+async fn rotate(
+    ctx: Data<AppContext<'_>>,
+    path: Path<(u32, u32, u32)>,
+    id: Identity,
+    input: Json<RotateInput>,
+) -> Result<impl Responder> {
+    let (organization_id, project_id, key_id) = path.into_inner();
+
+    let grace_minutes = input.grace_minutes.unwrap_or_default();
+
+    if grace_minutes > MAX_GRACE_MINUTES {
+        return Err(Error::field(
+            "grace_minutes",
+            format!("Grace period can be at most {} minutes", MAX_GRACE_MINUTES).into(),
+        ));
+    }
+
+    let project = find_project(&ctx, &id, organization_id, project_id, true).await?;
+    let old_key = find_active_key(&ctx, &project, key_id).await?;
+
+    let now = Utc::now().naive_utc();
+    let txn = ctx.db.begin().await?;
+
+    let new_key = project_keys::ActiveModel {
+        project_id: ActiveValue::set(project.project_id),
+        key: ActiveValue::set(generate_key()),
+        label: ActiveValue::set(old_key.label.clone()),
+        project_environment_id: ActiveValue::set(old_key.project_environment_id),
+        ..Default::default()
+    };
+
+    let new_key = new_key.insert(&txn).await?;
+
+    let mut old_row = old_key.clone().into_active_model();
+
+    if grace_minutes > 0 {
+        let expires = now + chrono::Duration::minutes(grace_minutes.into());
+        old_row.expires = ActiveValue::set(Some(old_key.expires.map_or(expires, |e| e.min(expires))));
+    } else {
+        old_row.revoked = ActiveValue::set(Some(now));
+    }
+
+    old_row.save(&txn).await?;
+
+    // the project's main key is the one shown to users
+    if project.api_key == old_key.key {
+        let mut project = project.into_active_model();
+        project.api_key = ActiveValue::set(new_key.key.clone());
+        project.save(&txn).await?;
+    }
+
+    txn.commit().await?;
+
+    Ok(Json(new_key))
+}
+
+#[post("/{key_id}/revoke")]
+async fn revoke(ctx: Data<AppContext<'_>>, path: Path<(u32, u32, u32)>, id: Identity) -> Result<impl Responder> {
+    let (organization_id, project_id, key_id) = path.into_inner();
+
+    let project = find_project(&ctx, &id, organization_id, project_id, true).await?;
+    let key = find_active_key(&ctx, &project, key_id).await?;
+
+    let txn = ctx.db.begin().await?;
+
+    let mut row = key.clone().into_active_model();
+    row.revoked = ActiveValue::set(Some(Utc::now().naive_utc()));
+    let key = row.save(&txn).await?.try_into_model()?;
+
+    replace_revoked_main_key(&txn, &project).await?;
+
+    txn.commit().await?;
+
+    Ok(Json(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test;
+    use serde_json::Value;
+
+    #[actix_web::test]
+    async fn test_project_keys() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Keys Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let default_key = res["api_key"].as_str().unwrap().to_string();
+
+        let keys_uri = format!("/api/organizations/1/projects/{}/keys", project_id);
+
+        let ingest = |key: &str| {
+            test::TestRequest::post()
+                .uri("/ingress")
+                .set_json(serde_json::json!({
+                    "key": key,
+                    "data": {
+                        "title": "Key test",
+                        "trace": "",
+                        "log": [],
+                        "os": "linux",
+                        "arch": "x86_64"
+                    }
+                }))
+                .to_request()
+        };
+
+        // the project key is listed
+        let req = test::TestRequest::get()
+            .uri(&keys_uri)
+            .cookie(sess.clone())
+            .to_request();
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res.as_array().unwrap().len(), 1);
+        assert_eq!(res[0]["key"], default_key.as_str());
+        let default_key_id = res[0]["project_key_id"].as_u64().unwrap();
+
+        // a second key for another deployment
+        let req = test::TestRequest::post()
+            .uri(&keys_uri)
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "label": "Staging" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let staging_key = res["key"].as_str().unwrap().to_string();
+        let staging_key_id = res["project_key_id"].as_u64().unwrap();
+
+        let res = test::call_service(&app, ingest(&staging_key)).await;
+        assert_eq!(res.status(), 200);
+        crate::process_queues(&ctx).await;
+
+        // events record the key they were sent with
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports?project_id={}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let report_id = res["reports"][0]["report"]["project_report_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports/{}/events", report_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["events"][0]["project_key_id"], staging_key_id);
+
+        // rotating the main key replaces it on the project
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}/rotate", keys_uri, default_key_id))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({}))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let rotated_key = res["key"].as_str().unwrap().to_string();
+        let rotated_key_id = res["project_key_id"].as_u64().unwrap();
+        assert_eq!(res["label"], "Default");
+
+        let res = test::call_service(&app, ingest(&default_key)).await;
+        assert_eq!(res.status(), 400);
+
+        let res = test::call_service(&app, ingest(&rotated_key)).await;
+        assert_eq!(res.status(), 200);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/organizations/1/projects/{}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["api_key"], rotated_key.as_str());
+
+        // revoked keys stop working, other keys are unaffected
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}/revoke", keys_uri, staging_key_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert!(res["revoked"].is_string());
+
+        let res = test::call_service(&app, ingest(&staging_key)).await;
+        assert_eq!(res.status(), 400);
+
+        let req = test::TestRequest::get()
+            .uri(&keys_uri)
+            .cookie(sess.clone())
+            .to_request();
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res.as_array().unwrap().len(), 3);
+        assert!(res[1]["last_used"].is_string());
+
+        // revoking the last active key leaves the project without a working main key
+        let req = test::TestRequest::post()
+            .uri(&format!("{}/{}/revoke", keys_uri, rotated_key_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/organizations/1/projects/{}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+        assert_ne!(api_key, rotated_key);
+
+        let res = test::call_service(&app, ingest(&api_key)).await;
+        assert_eq!(res.status(), 400);
+    }
+
+    #[actix_web::test]
+    async fn test_environment_keys() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Environment Keys Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        let ingest = |key: &str, env: Option<&str>| {
+            test::TestRequest::post()
+                .uri("/ingress")
+                .set_json(serde_json::json!({
+                    "key": key,
+                    "env": env,
+                    "data": {
+                        "title": "Environment test",
+                        "trace": "",
+                        "log": [],
+                        "os": "linux",
+                        "arch": "x86_64"
+                    }
+                }))
+                .to_request()
+        };
+
+        let report_environments = || {
+            test::TestRequest::get()
+                .uri(&format!("/api/reports?project_id={}", project_id))
+                .cookie(sess.clone())
+                .to_request()
+        };
+
+        let res = test::call_service(&app, ingest(&api_key, Some("production"))).await;
+        assert_eq!(res.status(), 200);
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::call_and_read_body_json(&app, report_environments()).await;
+        let production_id = res["reports"][0]["env"]["project_environment_id"].as_u64().unwrap();
+
+        // unknown environments are rejected once auto creation is disabled
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({
+                "project_id": project_id,
+                "name": "Environment Keys Project",
+                "auto_create_environments": false,
+            }))
+            .to_request();
+
+        test::call_service(&app, req).await;
+
+        let res = test::call_service(&app, ingest(&api_key, Some("qa"))).await;
+        assert_eq!(res.status(), 400);
+
+        // or go to the default environment
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({
+                "project_id": project_id,
+                "name": "Environment Keys Project",
+                "default_environment_id": production_id,
+            }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["auto_create_environments"], false);
+        assert_eq!(res["default_environment_id"], production_id);
+
+        let res = test::call_service(&app, ingest(&api_key, Some("qa"))).await;
+        assert_eq!(res.status(), 200);
+
+        // a key bound to production can't send events anywhere else
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/organizations/1/projects/{}/keys", project_id))
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "label": "Production only", "project_environment_id": production_id }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let production_key = res["key"].as_str().unwrap().to_string();
+
+        let res = test::call_service(&app, ingest(&production_key, Some("staging"))).await;
+        assert_eq!(res.status(), 400);
+
+        let res = test::call_service(&app, ingest(&production_key, None)).await;
+        assert_eq!(res.status(), 200);
+        crate::process_queues(&ctx).await;
+
+        // every event ended up in production
+        let res: Value = test::call_and_read_body_json(&app, report_environments()).await;
+        let reports = res["reports"].as_array().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0]["env"]["name"], "production");
+    }
+}
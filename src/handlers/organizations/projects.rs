@@ -3,17 +3,19 @@ use actix_web::{
     web::{Data, Json, Path},
     Responder,
 };
-use rand::{distr::Alphanumeric, prelude::*};
 use sea_orm::{prelude::*, ActiveValue, IntoActiveModel, JoinType, QuerySelect, QueryTrait, TryIntoModel};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use validator::Validate;
 
 use crate::entity::organization_users;
 use crate::entity::organizations;
 use crate::entity::prelude::*;
+use crate::entity::project_environments;
+use crate::entity::project_keys;
 use crate::entity::project_user_settings;
 use crate::entity::projects;
 
+use crate::grouping::GroupingStrategy;
 use crate::{AppContext, Error, Identity, Result};
 
 pub fn routes(cfg: &mut web::ServiceConfig) {
@@ -26,6 +28,14 @@ pub struct OrganizationProject {
     organization_id: u32,
     name: String,
     api_key: String,
+    grouping: String,
+    retention_events: Option<u32>,
+    retention_days: Option<u32>,
+    rate_limit: Option<u32>,
+    report_rate_limit: Option<u32>,
+    spike_protection: bool,
+    auto_create_environments: bool,
+    default_environment_id: Option<u32>,
     created: DateTime,
 }
 
@@ -36,6 +46,14 @@ impl From<projects::Model> for OrganizationProject {
             organization_id: project.organization_id,
             name: project.name,
             api_key: project.api_key,
+            grouping: project.grouping,
+            retention_events: project.retention_events,
+            retention_days: project.retention_days,
+            rate_limit: project.rate_limit,
+            report_rate_limit: project.report_rate_limit,
+            spike_protection: project.spike_protection > 0,
+            auto_create_environments: project.auto_create_environments > 0,
+            default_environment_id: project.default_environment_id,
             created: project.created,
         }
     }
@@ -70,6 +88,30 @@ struct ProjectInput {
     project_id: Option<u32>,
     #[validate(length(min = 1, max = 80, message = "Project name is required"))]
     name: String,
+    grouping: Option<String>,
+    // a missing field keeps the current setting, null falls back to the server defaults
+    #[serde(default, deserialize_with = "explicit_null")]
+    retention_events: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "explicit_null")]
+    retention_days: Option<Option<u32>>,
+    // events per minute, null removes the limit
+    #[serde(default, deserialize_with = "explicit_null")]
+    rate_limit: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "explicit_null")]
+    report_rate_limit: Option<Option<u32>>,
+    spike_protection: Option<bool>,
+    // when disabled, events for unknown environments go to the default environment or are rejected
+    auto_create_environments: Option<bool>,
+    #[serde(default, deserialize_with = "explicit_null")]
+    default_environment_id: Option<Option<u32>>,
+}
+
+fn explicit_null<'de, D, T>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 #[post("")]
@@ -116,15 +158,9 @@ async fn manage(
             .ok_or(Error::NotFound)?
             .into_active_model()
     } else {
-        let api_key: String = rand::rng()
-            .sample_iter(&Alphanumeric)
-            .take(32)
-            .map(char::from)
-            .collect();
-
         projects::ActiveModel {
             organization_id: ActiveValue::set(organization.organization_id),
-            api_key: ActiveValue::set(api_key),
+            api_key: ActiveValue::set(super::keys::generate_key()),
             ..Default::default()
         }
     };
@@ -133,6 +169,83 @@ async fn manage(
 
     project.name = ActiveValue::set(input.name);
 
+    if let Some(grouping) = input.grouping {
+        let Ok(strategy) = grouping.parse::<GroupingStrategy>() else {
+            return Err(Error::field("grouping", "Unknown grouping strategy".into()));
+        };
+
+        project.grouping = ActiveValue::set(strategy.to_string());
+    }
+
+    if let Some(retention_events) = input.retention_events {
+        if retention_events.is_some_and(|count| !(1..=10000).contains(&count)) {
+            return Err(Error::field(
+                "retention_events",
+                "Events per report must be between 1 and 10000".into(),
+            ));
+        }
+
+        project.retention_events = ActiveValue::set(retention_events);
+    }
+
+    if let Some(retention_days) = input.retention_days {
+        if retention_days.is_some_and(|days| !(1..=3650).contains(&days)) {
+            return Err(Error::field(
+                "retention_days",
+                "Retention must be between 1 and 3650 days".into(),
+            ));
+        }
+
+        project.retention_days = ActiveValue::set(retention_days);
+    }
+
+    if let Some(rate_limit) = input.rate_limit {
+        if rate_limit.is_some_and(|limit| !(1..=1_000_000).contains(&limit)) {
+            return Err(Error::field(
+                "rate_limit",
+                "Events per minute must be between 1 and 1000000".into(),
+            ));
+        }
+
+        project.rate_limit = ActiveValue::set(rate_limit);
+    }
+
+    if let Some(report_rate_limit) = input.report_rate_limit {
+        if report_rate_limit.is_some_and(|limit| !(1..=1_000_000).contains(&limit)) {
+            return Err(Error::field(
+                "report_rate_limit",
+                "Events per minute must be between 1 and 1000000".into(),
+            ));
+        }
+
+        project.report_rate_limit = ActiveValue::set(report_rate_limit);
+    }
+
+    if let Some(spike_protection) = input.spike_protection {
+        project.spike_protection = ActiveValue::set(spike_protection.into());
+    }
+
+    if let Some(auto_create_environments) = input.auto_create_environments {
+        project.auto_create_environments = ActiveValue::set(auto_create_environments.into());
+    }
+
+    if let Some(default_environment_id) = input.default_environment_id {
+        if let Some(project_environment_id) = default_environment_id {
+            // a new project has no environments yet
+            let ActiveValue::Unchanged(project_id) = project.project_id else {
+                return Err(Error::field("default_environment_id", "Environment not found".into()));
+            };
+
+            ProjectEnvironments::find_by_id(project_environment_id)
+                .filter(project_environments::Column::ProjectId.eq(project_id))
+                .one(&ctx.db)
+                .await?
+                .ok_or_else(|| Error::field("default_environment_id", "Environment not found".into()))?;
+        }
+
+        project.default_environment_id = ActiveValue::set(default_environment_id);
+    }
+
     let project = project.save(&ctx.db).await?.try_into_model()?;
 
     if is_new {
@@ -144,6 +257,15 @@ async fn manage(
         };
 
         project_user_settings.insert(&ctx.db).await?;
+
+        let project_key = project_keys::ActiveModel {
+            project_id: ActiveValue::set(project.project_id),
+            key: ActiveValue::set(project.api_key.clone()),
+            label: ActiveValue::set("Default".into()),
+            ..Default::default()
+        };
+
+        project_key.insert(&ctx.db).await?;
     }
 
     Ok(Json(OrganizationProject::from(project)))
@@ -233,6 +355,10 @@ mod tests {
             .set_json(serde_json::json!({
                 "project_id": project_id,
                 "name": "Test Project Updated",
+                "grouping": "frames",
+                "retention_events": 20,
+                "report_rate_limit": 100,
+                "spike_protection": true,
             }))
             .to_request();
 
@@ -246,6 +372,12 @@ mod tests {
 
         let resp: Value = test::call_and_read_body_json(&app, req).await;
         assert_eq!(resp[0]["name"], "Test Project Updated");
+        assert_eq!(resp[0]["grouping"], "frames");
+        assert_eq!(resp[0]["retention_events"], 20);
+        assert!(resp[0]["retention_days"].is_null());
+        assert!(resp[0]["rate_limit"].is_null());
+        assert_eq!(resp[0]["report_rate_limit"], 100);
+        assert_eq!(resp[0]["spike_protection"], true);
         assert_eq!(resp[0]["project_id"], project_id);
 
         //delete
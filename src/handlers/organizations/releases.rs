@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use actix_web::{
+    get, web,
+    web::{Data, Json, Path},
+    Responder,
+};
+use sea_orm::{prelude::*, QueryOrder, QuerySelect};
+use serde::Serialize;
+
+use crate::entity::prelude::*;
+use crate::entity::project_releases;
+use crate::entity::project_reports;
+use crate::entity::projects;
+
+use crate::{AppContext, Error, Identity, Result};
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list);
+}
+
+#[derive(Serialize, Debug)]
+struct Release {
+    #[serde(flatten)]
+    release: project_releases::Model,
+    /// Reports that were first seen in this release
+    new_reports: i64,
+}
+
+/// Releases of a project, most recently seen first
+#[get("")]
+async fn list(ctx: Data<AppContext<'_>>, path: Path<(u32, u32)>, id: Identity) -> Result<impl Responder> {
+    let (organization_id, project_id) = path.into_inner();
+
+    let user = id.user(&ctx).await?;
+    let _user_has_access = user.role(&ctx.db, organization_id).await?.ok_or(Error::LoginRequired)?;
+
+    let project = Projects::find_by_id(project_id)
+        .filter(projects::Column::OrganizationId.eq(organization_id))
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let releases = project
+        .find_related(ProjectReleases)
+        .order_by_desc(project_releases::Column::LastSeen)
+        .all(&ctx.db)
+        .await?;
+
+    let new_reports: HashMap<String, i64> = project
+        .find_related(ProjectReports)
+        .select_only()
+        .column(project_reports::Column::FirstVersion)
+        .column_as(project_reports::Column::ProjectReportId.count(), "count")
+        .filter(project_reports::Column::FirstVersion.is_not_null())
+        .group_by(project_reports::Column::FirstVersion)
+        .into_tuple::<(String, i64)>()
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .collect();
+
+    let releases: Vec<Release> = releases
+        .into_iter()
+        .map(|release| Release {
+            new_reports: new_reports.get(&release.version).copied().unwrap_or_default(),
+            release,
+        })
+        .collect();
+
+    Ok(Json(releases))
+}
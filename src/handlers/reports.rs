@@ -14,24 +14,39 @@ use chrono::prelude::*;
 use chrono::Days;
 use rust_decimal::prelude::*;
 use sea_orm::prelude::*;
-use sea_orm::sea_query::Alias;
-use sea_orm::{ActiveValue, Condition, IntoActiveModel, JoinType, Order, QueryOrder, QuerySelect, QueryTrait};
+use sea_orm::sea_query::{self, Alias};
+use sea_orm::{
+    ActiveValue, Condition, ConnectionTrait, IntoActiveModel, JoinType, Order, QueryOrder, QuerySelect, QueryTrait,
+    TransactionTrait, TryIntoModel,
+};
 use serde::{Deserialize, Serialize};
 use tokio_stream::{wrappers::BroadcastStream, StreamExt};
 
 use crate::entity::prelude::*;
 use crate::entity::{
-    organization_users, organizations, project_environments, project_report_events, project_report_stats,
-    project_reports, projects,
+    organization_users, organizations, project_environments, project_report_events, project_report_merges,
+    project_report_stats, project_reports, projects,
 };
 
+use crate::notifications::outbox;
+use crate::releases;
 use crate::{AppContext, Error, Identity, Result};
 
+// events returned per page of the events of a report
+const PAGE_SIZE: u64 = 10;
+
 pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg.service(list)
         .service(delete)
         .service(resolve)
+        .service(resolve_next_release)
+        .service(ignore)
+        .service(unignore)
         .service(subscribe)
+        .service(merge_reports)
+        .service(list_merges)
+        .service(unmerge_report)
+        .service(list_events)
         .service(get_report);
 }
 
@@ -56,12 +71,110 @@ struct Cursor {
     last_seen: NaiveDateTime,
 }
 
+/// Counters of a merged report, kept so they can be subtracted from the merge target on unmerge
+#[derive(Serialize, Deserialize, Debug)]
+struct MergedStat {
+    category: String,
+    name: String,
+    count: u32,
+    date: NaiveDateTime,
+}
+
+/// How long a report stays ignored, events are still counted in the meantime
+#[derive(Deserialize, Debug)]
+#[serde(tag = "until", rename_all = "snake_case")]
+enum IgnoreUntil {
+    Forever,
+    /// Until this many more events are received
+    Events {
+        count: u32,
+    },
+    /// For a number of minutes
+    Time {
+        minutes: u32,
+    },
+    /// Until an event from a release newer than the current latest one
+    NextRelease,
+}
+
+#[derive(Deserialize, Debug)]
+struct IgnoreInput {
+    report_ids: Vec<u32>,
+    #[serde(flatten)]
+    until: IgnoreUntil,
+}
+
+#[derive(Deserialize, Debug)]
+struct EventsQuery {
+    cursor: Option<u32>,
+}
+
+/// A single occurrence of a report, with the log and frames parsed
+#[derive(Serialize)]
+struct ReportEvent {
+    project_report_event_id: u32,
+    created: Option<NaiveDateTime>,
+    os: Option<String>,
+    arch: Option<String>,
+    version: Option<String>,
+    thread_id: Option<String>,
+    thread_name: Option<String>,
+    location_file: Option<String>,
+    location_line: Option<u32>,
+    location_column: Option<u32>,
+    received: Option<NaiveDateTime>,
+    client_ip_hash: Option<String>,
+    project_key_id: Option<u32>,
+    backtrace: Option<String>,
+    frames: serde_json::Value,
+    log: serde_json::Value,
+    tags: serde_json::Value,
+    context: serde_json::Value,
+}
+
+impl From<project_report_events::Model> for ReportEvent {
+    fn from(event: project_report_events::Model) -> Self {
+        let parse = |value: Option<String>| {
+            value
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(serde_json::Value::Null)
+        };
+
+        Self {
+            project_report_event_id: event.project_report_event_id,
+            created: event.created,
+            os: event.os,
+            arch: event.arch,
+            version: event.version,
+            thread_id: event.thread_id,
+            thread_name: event.thread_name,
+            location_file: event.location_file,
+            location_line: event.location_line,
+            location_column: event.location_column,
+            received: event.received,
+            client_ip_hash: event.client_ip_hash,
+            project_key_id: event.project_key_id,
+            backtrace: event.backtrace,
+            frames: parse(event.frames),
+            log: parse(event.log),
+            tags: parse(event.tags),
+            context: parse(event.context),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 struct ReportsQuery {
     cursor: Option<String>,
     project_id: Option<u32>,
     term: Option<String>,
     resolved: Option<u32>,
+    /// Ignored reports are hidden unless this is set to 1
+    ignored: Option<u32>,
+    /// Only reports with events tagged with "key:value"
+    tag: Option<String>,
+    /// Only reports of this environment, reports of hidden environments are left out otherwise
+    environment_id: Option<u32>,
 }
 
 #[get("")]
@@ -69,12 +182,31 @@ async fn list(ctx: Data<AppContext<'_>>, id: Identity, q: Query<ReportsQuery>) -
     let q = q.into_inner();
 
     let resolved = q.resolved.unwrap_or_default();
+    let ignored = q.ignored.unwrap_or_default();
     let cursor = q.cursor.as_ref().and_then(|v| serde_json::from_str::<Cursor>(v).ok());
+    let tag = q.tag.as_ref().and_then(|v| v.split_once(':'));
 
     let reports_and_envs = ProjectReports::find()
         .filter(organization_users::Column::UserId.eq(id.user_id))
         .filter(project_reports::Column::IsResolved.eq(resolved))
+        .filter(project_reports::Column::IsIgnored.eq(ignored))
         .apply_if(q.project_id, |query, v| query.filter(projects::Column::ProjectId.eq(v)))
+        .filter(match q.environment_id {
+            Some(v) => Condition::all().add(project_reports::Column::ProjectEnvironmentId.eq(v)),
+            None => Condition::any()
+                .add(project_environments::Column::IsHidden.eq(0))
+                .add(project_environments::Column::IsHidden.is_null()),
+        })
+        .apply_if(tag, |query, (key, value)| {
+            let tagged_reports = sea_query::Query::select()
+                .column(project_report_stats::Column::ProjectReportId)
+                .from(ProjectReportStats)
+                .and_where(project_report_stats::Column::Category.eq(format!("tag:{}", key)))
+                .and_where(project_report_stats::Column::Name.eq(value))
+                .to_owned();
+
+            query.filter(project_reports::Column::ProjectReportId.in_subquery(tagged_reports))
+        })
         .apply_if(q.term.as_ref().filter(|v| !v.is_empty()), |query, v| {
             let v = v.replace(' ', "%");
 
@@ -191,17 +323,99 @@ async fn resolve(ctx: Data<AppContext<'_>>, id: Identity, report_ids: Json<Vec<u
         .all(&ctx.db)
         .await?;
 
+    let txn = ctx.db.begin().await?;
+
+    // incidents are only closed for reports that were open
+    let newly_resolved = ProjectReports::find()
+        .filter(project_reports::Column::ProjectReportId.is_in(owned_reports.clone()))
+        .filter(project_reports::Column::IsResolved.eq(0))
+        .all(&txn)
+        .await?;
+
     let res = ProjectReports::update_many()
         .col_expr(project_reports::Column::IsResolved, Expr::value(1))
+        .col_expr(
+            project_reports::Column::ResolvedInVersion,
+            Expr::value(Option::<String>::None),
+        )
         .filter(project_reports::Column::ProjectReportId.is_in(owned_reports))
-        .exec(&ctx.db)
+        .exec(&txn)
         .await?;
 
+    outbox::enqueue_resolved(&txn, &newly_resolved).await?;
+
+    txn.commit().await?;
+
+    ctx.notification_queue.notify_waiters();
+
     Ok(Json(serde_json::json!({
         "deleted": res.rows_affected,
     })))
 }
 
+/// Resolves reports as fixed in the next release, events from the current or older releases won't reopen them
+#[post("/resolve-next-release")]
+async fn resolve_next_release(
+    ctx: Data<AppContext<'_>>,
+    id: Identity,
+    report_ids: Json<Vec<u32>>,
+) -> Result<impl Responder> {
+    let report_ids = report_ids.into_inner();
+
+    // make sure the user owns those reports
+    let owned_reports: Vec<(u32, u32)> = ProjectReports::find()
+        .select_only()
+        .column(project_reports::Column::ProjectReportId)
+        .column(project_reports::Column::ProjectId)
+        .filter(project_reports::Column::ProjectReportId.is_in(report_ids))
+        .filter(organization_users::Column::UserId.eq(id.user_id))
+        .join(JoinType::InnerJoin, project_reports::Relation::Projects.def())
+        .join(JoinType::InnerJoin, projects::Relation::Organizations.def())
+        .join(JoinType::InnerJoin, organizations::Relation::OrganizationUsers.def())
+        .into_tuple()
+        .all(&ctx.db)
+        .await?;
+
+    let mut by_project: HashMap<u32, Vec<u32>> = HashMap::new();
+
+    for (report_id, project_id) in owned_reports {
+        by_project.entry(project_id).or_default().push(report_id);
+    }
+
+    let mut resolved = 0;
+    let txn = ctx.db.begin().await?;
+
+    for (project_id, report_ids) in by_project {
+        // without any releases this is the same as a regular resolve
+        let latest_release = releases::latest(&txn, project_id).await?;
+
+        let newly_resolved = ProjectReports::find()
+            .filter(project_reports::Column::ProjectReportId.is_in(report_ids.clone()))
+            .filter(project_reports::Column::IsResolved.eq(0))
+            .all(&txn)
+            .await?;
+
+        let res = ProjectReports::update_many()
+            .col_expr(project_reports::Column::IsResolved, Expr::value(1))
+            .col_expr(project_reports::Column::ResolvedInVersion, Expr::value(latest_release))
+            .filter(project_reports::Column::ProjectReportId.is_in(report_ids))
+            .exec(&txn)
+            .await?;
+
+        resolved += res.rows_affected;
+
+        outbox::enqueue_resolved(&txn, &newly_resolved).await?;
+    }
+
+    txn.commit().await?;
+
+    ctx.notification_queue.notify_waiters();
+
+    Ok(Json(serde_json::json!({
+        "resolved": resolved,
+    })))
+}
+
 #[get("/subscribe")]
 async fn subscribe(ctx: Data<AppContext<'_>>, id: Identity, q: Query<ReportsQuery>) -> Result<impl Responder> {
     let rx = ctx.notifications.subscribe();
@@ -248,6 +462,423 @@ async fn subscribe(ctx: Data<AppContext<'_>>, id: Identity, q: Query<ReportsQuer
     Ok(sse::Sse::from_infallible_stream(stream).with_keep_alive(Duration::from_secs(5)))
 }
 
+#[post("/ignore")]
+async fn ignore(ctx: Data<AppContext<'_>>, id: Identity, input: Json<IgnoreInput>) -> Result<impl Responder> {
+    let input = input.into_inner();
+
+    let (condition, until, events_left) = match input.until {
+        IgnoreUntil::Forever => ("forever", None, None),
+        IgnoreUntil::Events { count } if count > 0 => ("events", None, Some(count)),
+        IgnoreUntil::Time { minutes } if minutes > 0 => (
+            "time",
+            Some(Utc::now().naive_utc() + chrono::Duration::minutes(minutes.into())),
+            None,
+        ),
+        IgnoreUntil::NextRelease => ("next_release", None, None),
+        _ => return Err(Error::new("The ignore condition must be greater than zero")),
+    };
+
+    // make sure the user owns those reports
+    let owned_reports: Vec<(u32, u32)> = ProjectReports::find()
+        .select_only()
+        .column(project_reports::Column::ProjectReportId)
+        .column(project_reports::Column::ProjectId)
+        .filter(project_reports::Column::ProjectReportId.is_in(input.report_ids))
+        .filter(organization_users::Column::UserId.eq(id.user_id))
+        .join(JoinType::InnerJoin, project_reports::Relation::Projects.def())
+        .join(JoinType::InnerJoin, projects::Relation::Organizations.def())
+        .join(JoinType::InnerJoin, organizations::Relation::OrganizationUsers.def())
+        .into_tuple()
+        .all(&ctx.db)
+        .await?;
+
+    let mut by_project: HashMap<u32, Vec<u32>> = HashMap::new();
+
+    for (report_id, project_id) in owned_reports {
+        by_project.entry(project_id).or_default().push(report_id);
+    }
+
+    let mut ignored = 0;
+
+    for (project_id, report_ids) in by_project {
+        let ignored_in_version = if condition == "next_release" {
+            releases::latest(&ctx.db, project_id).await?
+        } else {
+            None
+        };
+
+        let res = ProjectReports::update_many()
+            .col_expr(project_reports::Column::IsIgnored, Expr::value(1))
+            .col_expr(project_reports::Column::IgnoreCondition, Expr::value(condition))
+            .col_expr(project_reports::Column::IgnoredUntil, Expr::value(until))
+            .col_expr(project_reports::Column::IgnoredEventsLeft, Expr::value(events_left))
+            .col_expr(
+                project_reports::Column::IgnoredInVersion,
+                Expr::value(ignored_in_version),
+            )
+            .filter(project_reports::Column::ProjectReportId.is_in(report_ids))
+            .exec(&ctx.db)
+            .await?;
+
+        ignored += res.rows_affected;
+    }
+
+    Ok(Json(serde_json::json!({
+        "ignored": ignored,
+    })))
+}
+
+#[post("/unignore")]
+async fn unignore(ctx: Data<AppContext<'_>>, id: Identity, report_ids: Json<Vec<u32>>) -> Result<impl Responder> {
+    let report_ids = report_ids.into_inner();
+
+    // make sure the user owns those reports
+    let owned_reports: Vec<u32> = ProjectReports::find()
+        .select_only()
+        .column(project_reports::Column::ProjectReportId)
+        .filter(project_reports::Column::ProjectReportId.is_in(report_ids))
+        .filter(organization_users::Column::UserId.eq(id.user_id))
+        .join(JoinType::InnerJoin, project_reports::Relation::Projects.def())
+        .join(JoinType::InnerJoin, projects::Relation::Organizations.def())
+        .join(JoinType::InnerJoin, organizations::Relation::OrganizationUsers.def())
+        .into_tuple()
+        .all(&ctx.db)
+        .await?;
+
+    let res = ProjectReports::update_many()
+        .col_expr(project_reports::Column::IsIgnored, Expr::value(0))
+        .col_expr(
+            project_reports::Column::IgnoreCondition,
+            Expr::value(Option::<String>::None),
+        )
+        .col_expr(
+            project_reports::Column::IgnoredUntil,
+            Expr::value(Option::<NaiveDateTime>::None),
+        )
+        .col_expr(
+            project_reports::Column::IgnoredEventsLeft,
+            Expr::value(Option::<u32>::None),
+        )
+        .col_expr(
+            project_reports::Column::IgnoredInVersion,
+            Expr::value(Option::<String>::None),
+        )
+        .filter(project_reports::Column::ProjectReportId.is_in(owned_reports))
+        .exec(&ctx.db)
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "unignored": res.rows_affected,
+    })))
+}
+
+async fn find_owned_report(ctx: &AppContext<'_>, id: &Identity, report_id: u32) -> Result<project_reports::Model> {
+    let report = ProjectReports::find_by_id(report_id)
+        .filter(organization_users::Column::UserId.eq(id.user_id))
+        .join(JoinType::InnerJoin, project_reports::Relation::Projects.def())
+        .join(JoinType::InnerJoin, projects::Relation::Organizations.def())
+        .join(JoinType::InnerJoin, organizations::Relation::OrganizationUsers.def())
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(report)
+}
+
+/// Merges the given reports of the same project into the report in the path.
+/// Their events and counters are moved over, and future events with their uid land in the target report.
+#[post("/{report_id}/merge")]
+async fn merge_reports(
+    ctx: Data<AppContext<'_>>,
+    id: Identity,
+    path: Path<u32>,
+    report_ids: Json<Vec<u32>>,
+) -> Result<impl Responder> {
+    let target = find_owned_report(&ctx, &id, path.into_inner()).await?;
+
+    // keep the ingress queue from writing into the reports while they are moved
+    let _lock = ctx.locked_projects.lock(target.project_id).await;
+
+    let sources = ProjectReports::find()
+        .filter(project_reports::Column::ProjectReportId.is_in(report_ids.into_inner()))
+        .filter(project_reports::Column::ProjectReportId.ne(target.project_report_id))
+        .filter(project_reports::Column::ProjectId.eq(target.project_id))
+        .all(&ctx.db)
+        .await?;
+
+    if sources.is_empty() {
+        return Err(Error::new(
+            "Select at least one other report of the same project to merge",
+        ));
+    }
+
+    let txn = ctx.db.begin().await?;
+    let mut last_seen = target.last_seen;
+    let mut merges = vec![];
+
+    for source in &sources {
+        let stats: Vec<MergedStat> = source
+            .find_related(ProjectReportStats)
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|stat| MergedStat {
+                category: stat.category,
+                name: stat.name,
+                count: stat.count,
+                date: stat.date,
+            })
+            .collect();
+
+        let merge = project_report_merges::ActiveModel {
+            project_report_id: ActiveValue::set(target.project_report_id),
+            project_environment_id: ActiveValue::set(source.project_environment_id),
+            uid: ActiveValue::set(source.uid.clone()),
+            title: ActiveValue::set(source.title.clone()),
+            last_seen: ActiveValue::set(source.last_seen),
+            is_seen: ActiveValue::set(source.is_seen),
+            is_resolved: ActiveValue::set(source.is_resolved),
+            report_created: ActiveValue::set(source.created),
+            stats: ActiveValue::set(serde_json::to_string(&stats)?),
+            resolved_in_version: ActiveValue::set(source.resolved_in_version.clone()),
+            is_ignored: ActiveValue::set(source.is_ignored),
+            ignore_condition: ActiveValue::set(source.ignore_condition.clone()),
+            ignored_until: ActiveValue::set(source.ignored_until),
+            ignored_events_left: ActiveValue::set(source.ignored_events_left),
+            ignored_in_version: ActiveValue::set(source.ignored_in_version.clone()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        // the source report goes away, incidents opened for it are closed
+        if source.is_resolved == 0 {
+            merges.push(merge.clone());
+        }
+
+        for stat in &stats {
+            add_report_stat(&txn, target.project_report_id, stat).await?;
+        }
+
+        // events already moved by an earlier merge stay with that merge
+        ProjectReportEvents::update_many()
+            .col_expr(
+                project_report_events::Column::ProjectReportMergeId,
+                Expr::value(merge.project_report_merge_id),
+            )
+            .filter(project_report_events::Column::ProjectReportId.eq(source.project_report_id))
+            .filter(project_report_events::Column::ProjectReportMergeId.is_null())
+            .exec(&txn)
+            .await?;
+
+        ProjectReportEvents::update_many()
+            .col_expr(
+                project_report_events::Column::ProjectReportId,
+                Expr::value(target.project_report_id),
+            )
+            .filter(project_report_events::Column::ProjectReportId.eq(source.project_report_id))
+            .exec(&txn)
+            .await?;
+
+        // reports previously merged into the source follow it
+        ProjectReportMerges::update_many()
+            .col_expr(
+                project_report_merges::Column::ProjectReportId,
+                Expr::value(target.project_report_id),
+            )
+            .filter(project_report_merges::Column::ProjectReportId.eq(source.project_report_id))
+            .exec(&txn)
+            .await?;
+
+        last_seen = last_seen.max(source.last_seen);
+
+        source.clone().delete(&txn).await?;
+    }
+
+    let mut target = target.into_active_model();
+    target.last_seen = ActiveValue::set(last_seen);
+    let target = target.update(&txn).await?;
+
+    outbox::enqueue_merged(&txn, &target, &merges).await?;
+
+    txn.commit().await?;
+
+    ctx.notification_queue.notify_waiters();
+
+    Ok(Json(serde_json::json!({
+        "report": target,
+        "merged": sources.len(),
+    })))
+}
+
+#[get("/{report_id}/merges")]
+async fn list_merges(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
+    let report = find_owned_report(&ctx, &id, path.into_inner()).await?;
+
+    let merges = report
+        .find_related(ProjectReportMerges)
+        .order_by_desc(project_report_merges::Column::ProjectReportMergeId)
+        .all(&ctx.db)
+        .await?;
+
+    Ok(Json(merges))
+}
+
+/// Splits a merged report back out with its own events and counters.
+/// Counters of events received through the alias after the merge stay with the target report.
+#[post("/{report_id}/unmerge/{merge_id}")]
+async fn unmerge_report(ctx: Data<AppContext<'_>>, id: Identity, path: Path<(u32, u32)>) -> Result<impl Responder> {
+    let (report_id, merge_id) = path.into_inner();
+
+    let target = find_owned_report(&ctx, &id, report_id).await?;
+
+    let _lock = ctx.locked_projects.lock(target.project_id).await;
+
+    let merge = ProjectReportMerges::find_by_id(merge_id)
+        .filter(project_report_merges::Column::ProjectReportId.eq(target.project_report_id))
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let stats: Vec<MergedStat> = serde_json::from_str(&merge.stats)?;
+
+    let txn = ctx.db.begin().await?;
+
+    // the alias has to go before the uid can be used by a report again
+    merge.clone().delete(&txn).await?;
+
+    let report = project_reports::ActiveModel {
+        project_id: ActiveValue::set(target.project_id),
+        project_environment_id: ActiveValue::set(merge.project_environment_id),
+        uid: ActiveValue::set(merge.uid),
+        title: ActiveValue::set(merge.title),
+        last_seen: ActiveValue::set(merge.last_seen),
+        is_seen: ActiveValue::set(merge.is_seen),
+        is_resolved: ActiveValue::set(merge.is_resolved),
+        created: ActiveValue::set(merge.report_created),
+        resolved_in_version: ActiveValue::set(merge.resolved_in_version),
+        is_ignored: ActiveValue::set(merge.is_ignored),
+        ignore_condition: ActiveValue::set(merge.ignore_condition),
+        ignored_until: ActiveValue::set(merge.ignored_until),
+        ignored_events_left: ActiveValue::set(merge.ignored_events_left),
+        ignored_in_version: ActiveValue::set(merge.ignored_in_version),
+        ..Default::default()
+    }
+    .save(&txn)
+    .await?
+    .try_into_model()?;
+
+    ProjectReportEvents::update_many()
+        .col_expr(
+            project_report_events::Column::ProjectReportId,
+            Expr::value(report.project_report_id),
+        )
+        .col_expr(
+            project_report_events::Column::ProjectReportMergeId,
+            Expr::value(Option::<u32>::None),
+        )
+        .filter(project_report_events::Column::ProjectReportMergeId.eq(merge.project_report_merge_id))
+        .exec(&txn)
+        .await?;
+
+    for stat in &stats {
+        add_report_stat(&txn, report.project_report_id, stat).await?;
+
+        ProjectReportStats::update_many()
+            .col_expr(
+                project_report_stats::Column::Count,
+                Expr::col(project_report_stats::Column::Count).sub(stat.count),
+            )
+            .filter(project_report_stats::Column::ProjectReportId.eq(target.project_report_id))
+            .filter(project_report_stats::Column::Category.eq(&stat.category))
+            .filter(project_report_stats::Column::Name.eq(&stat.name))
+            .filter(project_report_stats::Column::Date.eq(stat.date))
+            .filter(project_report_stats::Column::Count.gte(stat.count))
+            .exec(&txn)
+            .await?;
+    }
+
+    ProjectReportStats::delete_many()
+        .filter(project_report_stats::Column::ProjectReportId.eq(target.project_report_id))
+        .filter(project_report_stats::Column::Count.eq(0))
+        .exec(&txn)
+        .await?;
+
+    txn.commit().await?;
+
+    Ok(Json(report))
+}
+
+/// Retained events of a report, newest first
+#[get("/{report_id}/events")]
+async fn list_events(
+    ctx: Data<AppContext<'_>>,
+    id: Identity,
+    path: Path<u32>,
+    q: Query<EventsQuery>,
+) -> Result<impl Responder> {
+    let report = find_owned_report(&ctx, &id, path.into_inner()).await?;
+
+    let events = report
+        .find_related(ProjectReportEvents)
+        .apply_if(q.cursor, |query, cursor| {
+            query.filter(project_report_events::Column::ProjectReportEventId.lt(cursor))
+        })
+        .order_by_desc(project_report_events::Column::ProjectReportEventId)
+        .limit(PAGE_SIZE + 1)
+        .all(&ctx.db)
+        .await?;
+
+    // an extra event means there is another page, which starts after the last returned one
+    let next = if events.len() as u64 > PAGE_SIZE {
+        events.get(PAGE_SIZE as usize - 1).map(|e| e.project_report_event_id)
+    } else {
+        None
+    };
+
+    let events: Vec<ReportEvent> = events
+        .into_iter()
+        .take(PAGE_SIZE as usize)
+        .map(ReportEvent::from)
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "events": events,
+        "next": next,
+    })))
+}
+
+async fn add_report_stat(db: &impl ConnectionTrait, report_id: u32, stat: &MergedStat) -> Result<()> {
+    let row = project_report_stats::ActiveModel {
+        project_report_id: ActiveValue::set(report_id),
+        category: ActiveValue::set(stat.category.clone()),
+        name: ActiveValue::set(stat.name.clone()),
+        count: ActiveValue::set(stat.count),
+        date: ActiveValue::set(stat.date),
+        spiking: ActiveValue::set(0),
+        ..Default::default()
+    };
+
+    ProjectReportStats::insert(row)
+        .on_conflict(
+            sea_query::OnConflict::columns([
+                project_report_stats::Column::ProjectReportId,
+                project_report_stats::Column::Category,
+                project_report_stats::Column::Name,
+                project_report_stats::Column::Date,
+            ])
+            .value(
+                project_report_stats::Column::Count,
+                Expr::col(project_report_stats::Column::Count).add(stat.count),
+            )
+            .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
 #[get("/{report_id}")]
 async fn get_report(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) -> Result<impl Responder> {
     let report_id = path.into_inner();
@@ -306,7 +937,8 @@ async fn get_report(ctx: Data<AppContext<'_>>, id: Identity, path: Path<u32>) ->
         .filter(project_report_events::Column::ProjectReportId.eq(report_id))
         .order_by(project_report_events::Column::ProjectReportEventId, Order::Desc)
         .one(&ctx.db)
-        .await?;
+        .await?
+        .map(ReportEvent::from);
 
     Ok(Json(serde_json::json!({
         "project": project,
@@ -373,3 +1005,628 @@ async fn get_dataset(
 
     Ok((dataset, names))
 }
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test;
+    use sea_orm::prelude::*;
+    use serde_json::Value;
+
+    use crate::entity::prelude::*;
+    use crate::entity::project_report_events;
+
+    #[actix_web::test]
+    async fn test_merge_reports() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Merge Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        let ingest = |title: &str| {
+            test::TestRequest::post()
+                .uri("/ingress")
+                .set_json(serde_json::json!({
+                    "key": api_key,
+                    "data": {
+                        "title": title,
+                        "trace": "",
+                        "log": [],
+                        "os": "linux",
+                        "arch": "x86_64"
+                    }
+                }))
+                .to_request()
+        };
+
+        let list_reports = || {
+            test::TestRequest::get()
+                .uri(&format!("/api/reports?project_id={}", project_id))
+                .cookie(sess.clone())
+                .to_request()
+        };
+
+        let total_events = |report: &Value| -> u64 {
+            report["daily_events"]
+                .as_object()
+                .unwrap()
+                .values()
+                .map(|count| count.as_u64().unwrap())
+                .sum()
+        };
+
+        test::call_service(&app, ingest("Connection refused")).await;
+        crate::process_queues(&ctx).await;
+        test::call_service(&app, ingest("Connection reset")).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::call_and_read_body_json(&app, list_reports()).await;
+        let reports = res["reports"].as_array().unwrap();
+        assert_eq!(reports.len(), 2);
+
+        let target_id = reports[1]["report"]["project_report_id"].as_u64().unwrap();
+        let source_id = reports[0]["report"]["project_report_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/reports/ignore")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "report_ids": [source_id], "until": "forever" }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+
+        // merge
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/reports/{}/merge", target_id))
+            .cookie(sess.clone())
+            .set_json(vec![source_id])
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["merged"], 1);
+
+        // events with the merged uid land in the target report
+        test::call_service(&app, ingest("Connection reset")).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::call_and_read_body_json(&app, list_reports()).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 1);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports/{}", target_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(total_events(&res), 3);
+
+        // unmerge
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports/{}/merges", target_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res.as_array().unwrap().len(), 1);
+        let merge_id = res[0]["project_report_merge_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/reports/{}/unmerge/{}", target_id, merge_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["title"], "Connection reset in Unknown");
+        assert_eq!(res["is_ignored"], 1);
+        assert_eq!(res["ignore_condition"], "forever");
+        let split_id = res["project_report_id"].as_u64().unwrap();
+
+        // the unmerged report is ignored again, like before the merge
+        let res: Value = test::call_and_read_body_json(&app, list_reports()).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 1);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports/{}", split_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(total_events(&res), 1);
+        assert!(res["last_event"].is_object());
+    }
+
+    #[actix_web::test]
+    async fn test_list_events() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Events Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        let events: Vec<Value> = (0..12)
+            .map(|idx| {
+                serde_json::json!({
+                    "key": api_key,
+                    "data": {
+                        "title": "Out of memory",
+                        "trace": "",
+                        "log": [{ "ts": 1, "lvl": 1, "msg": format!("allocation {}", idx) }],
+                        "os": if idx % 2 == 0 { "linux" } else { "windows" },
+                        "arch": "x86_64",
+                        "ver": "1.0.0",
+                        "tid": format!("ThreadId({})", idx),
+                        "tname": "worker"
+                    }
+                })
+            })
+            .collect();
+
+        let req = test::TestRequest::post()
+            .uri("/ingress/batch")
+            .set_json(events)
+            .to_request();
+
+        test::call_service(&app, req).await;
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports?project_id={}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let report_id = res["reports"][0]["report"]["project_report_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports/{}/events", report_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let events = res["events"].as_array().unwrap();
+        assert_eq!(events.len(), 10);
+        assert_eq!(events[0]["os"], "windows");
+        assert_eq!(events[0]["thread_id"], "ThreadId(11)");
+        assert_eq!(events[0]["thread_name"], "worker");
+        assert_eq!(events[0]["version"], "1.0.0");
+        assert_eq!(events[0]["log"][0]["msg"], "allocation 11");
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports/{}/events?cursor={}", report_id, res["next"]))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let events = res["events"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1]["os"], "linux");
+        assert!(res["next"].is_null());
+    }
+
+    #[actix_web::test]
+    async fn test_tags() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Tags Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        let event = |title: &str, tenant: &str| {
+            serde_json::json!({
+                "key": api_key,
+                "data": {
+                    "title": title,
+                    "trace": "",
+                    "log": [],
+                    "os": "linux",
+                    "arch": "x86_64",
+                    "tags": { "tenant": tenant, "host": "web-1" },
+                    "context": { "request": { "id": "abc", "path": "/orders" } }
+                }
+            })
+        };
+
+        let req = test::TestRequest::post()
+            .uri("/ingress/batch")
+            .set_json(vec![event("Order failed", "acme"), event("Payment failed", "globex")])
+            .to_request();
+
+        test::call_service(&app, req).await;
+        crate::process_queues(&ctx).await;
+
+        let list_reports = |tag: &str| {
+            test::TestRequest::get()
+                .uri(&format!("/api/reports?project_id={}&tag={}", project_id, tag))
+                .cookie(sess.clone())
+                .to_request()
+        };
+
+        let res: Value = test::call_and_read_body_json(&app, list_reports("tenant:acme")).await;
+        let reports = res["reports"].as_array().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0]["report"]["title"], "Order failed in Unknown");
+
+        let res: Value = test::call_and_read_body_json(&app, list_reports("host:web-1")).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 2);
+
+        let res: Value = test::call_and_read_body_json(&app, list_reports("tenant:initech")).await;
+        assert!(res["reports"].as_array().unwrap().is_empty());
+
+        let report_id = reports[0]["report"]["project_report_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports/{}/events", report_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["events"][0]["tags"]["tenant"], "acme");
+        assert_eq!(res["events"][0]["context"]["request"]["path"], "/orders");
+    }
+
+    #[actix_web::test]
+    async fn test_resolve_next_release() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Releases Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        let ingest = |version: &str| {
+            test::TestRequest::post()
+                .uri("/ingress")
+                .set_json(serde_json::json!({
+                    "key": api_key,
+                    "data": {
+                        "title": "Index out of bounds",
+                        "trace": "",
+                        "log": [],
+                        "os": "linux",
+                        "arch": "x86_64",
+                        "ver": version
+                    }
+                }))
+                .to_request()
+        };
+
+        let get_report = |resolved: u32| {
+            test::TestRequest::get()
+                .uri(&format!("/api/reports?project_id={}&resolved={}", project_id, resolved))
+                .cookie(sess.clone())
+                .to_request()
+        };
+
+        test::call_service(&app, ingest("1.0.0")).await;
+        crate::process_queues(&ctx).await;
+        test::call_service(&app, ingest("1.1.0")).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::call_and_read_body_json(&app, get_report(0)).await;
+        let report = &res["reports"][0]["report"];
+        assert_eq!(report["first_version"], "1.0.0");
+        assert_eq!(report["last_version"], "1.1.0");
+        let report_id = report["project_report_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/reports/resolve-next-release")
+            .cookie(sess.clone())
+            .set_json(vec![report_id])
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["resolved"], 1);
+
+        // the fix isn't deployed yet, events from the current release don't reopen the report
+        test::call_service(&app, ingest("1.1.0")).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::call_and_read_body_json(&app, get_report(1)).await;
+        assert_eq!(res["reports"][0]["report"]["resolved_in_version"], "1.1.0");
+
+        // a newer release is a regression
+        test::call_service(&app, ingest("v1.2.0")).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::call_and_read_body_json(&app, get_report(0)).await;
+        let report = &res["reports"][0]["report"];
+        assert_eq!(report["is_resolved"], 0);
+        assert!(report["resolved_in_version"].is_null());
+        assert_eq!(report["last_version"], "v1.2.0");
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/organizations/1/projects/{}/releases", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let releases = res.as_array().unwrap();
+        assert_eq!(releases.len(), 3);
+
+        let first = releases.iter().find(|r| r["version"] == "1.0.0").unwrap();
+        assert_eq!(first["new_reports"], 1);
+    }
+
+    #[actix_web::test]
+    async fn test_ignore() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Ignore Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        let ingest = || {
+            test::TestRequest::post()
+                .uri("/ingress")
+                .set_json(serde_json::json!({
+                    "key": api_key,
+                    "data": {
+                        "title": "Connection reset",
+                        "trace": "",
+                        "log": [],
+                        "os": "linux",
+                        "arch": "x86_64"
+                    }
+                }))
+                .to_request()
+        };
+
+        let get_reports = |ignored: u32| {
+            test::TestRequest::get()
+                .uri(&format!("/api/reports?project_id={}&ignored={}", project_id, ignored))
+                .cookie(sess.clone())
+                .to_request()
+        };
+
+        test::call_service(&app, ingest()).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::call_and_read_body_json(&app, get_reports(0)).await;
+        let report_id = res["reports"][0]["report"]["project_report_id"].as_u64().unwrap();
+
+        // the count must be positive
+        let req = test::TestRequest::post()
+            .uri("/api/reports/ignore")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "report_ids": [report_id], "until": "events", "count": 0 }))
+            .to_request();
+
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        let req = test::TestRequest::post()
+            .uri("/api/reports/ignore")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "report_ids": [report_id], "until": "events", "count": 1 }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["ignored"], 1);
+
+        // ignored reports are hidden by default but still counted
+        test::call_service(&app, ingest()).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::call_and_read_body_json(&app, get_reports(0)).await;
+        assert!(res["reports"].as_array().unwrap().is_empty());
+
+        let res: Value = test::call_and_read_body_json(&app, get_reports(1)).await;
+        let report = &res["reports"][0]["report"];
+        assert_eq!(report["ignored_events_left"], 0);
+
+        // once the count runs out the report shows up again
+        test::call_service(&app, ingest()).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::call_and_read_body_json(&app, get_reports(0)).await;
+        let report = &res["reports"][0]["report"];
+        assert_eq!(report["is_ignored"], 0);
+        assert!(report["ignore_condition"].is_null());
+
+        // forever stays ignored until unignored
+        let req = test::TestRequest::post()
+            .uri("/api/reports/ignore")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "report_ids": [report_id], "until": "forever" }))
+            .to_request();
+        test::call_service(&app, req).await;
+
+        test::call_service(&app, ingest()).await;
+        crate::process_queues(&ctx).await;
+
+        let res: Value = test::call_and_read_body_json(&app, get_reports(1)).await;
+        assert_eq!(res["reports"][0]["report"]["ignore_condition"], "forever");
+
+        let req = test::TestRequest::post()
+            .uri("/api/reports/unignore")
+            .cookie(sess.clone())
+            .set_json(vec![report_id])
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["unignored"], 1);
+
+        let res: Value = test::call_and_read_body_json(&app, get_reports(0)).await;
+        assert_eq!(res["reports"].as_array().unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_resolve_incidents() {
+        let (app, sess, ctx) = crate::test_app_with_context().await.unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/api/organizations/1/projects")
+            .cookie(sess.clone())
+            .set_json(serde_json::json!({ "name": "Incidents Project" }))
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let project_id = res["project_id"].as_u64().unwrap();
+        let api_key = res["api_key"].as_str().unwrap().to_string();
+
+        let channels = [
+            (
+                "pagerduty",
+                serde_json::json!({ "routing_key": "0123456789abcdef0123456789abcdef" }),
+            ),
+            (
+                "opsgenie",
+                serde_json::json!({ "api_key": "test-key", "priority": "P2" }),
+            ),
+        ];
+
+        for (channel, config) in channels {
+            let req = test::TestRequest::post()
+                .uri(&format!("/api/notifications/{}/channels/{}", project_id, channel))
+                .cookie(sess.clone())
+                .set_json(serde_json::json!({ "config": config }))
+                .to_request();
+
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), 200);
+        }
+
+        let req = test::TestRequest::post()
+            .uri("/ingress")
+            .set_json(serde_json::json!({
+                "key": api_key,
+                "data": {
+                    "title": "Connection pool exhausted",
+                    "trace": "",
+                    "log": [],
+                    "os": "linux",
+                    "arch": "x86_64",
+                    "ver": "1.0.0"
+                }
+            }))
+            .to_request();
+
+        test::call_service(&app, req).await;
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports?project_id={}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let report_id = res["reports"][0]["report"]["project_report_id"].as_u64().unwrap();
+
+        let resolve = || {
+            test::TestRequest::post()
+                .uri("/api/reports/resolve")
+                .cookie(sess.clone())
+                .set_json(vec![report_id])
+                .to_request()
+        };
+
+        let list_deliveries = || {
+            test::TestRequest::get()
+                .uri(&format!("/api/notifications/{}/deliveries", project_id))
+                .cookie(sess.clone())
+                .to_request()
+        };
+
+        let count_resolved = |res: &Value| {
+            res["deliveries"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|d| d["report_status"] == "resolved")
+                .count()
+        };
+
+        // incidents are closed with the report uid, even once retention deleted its events
+        ProjectReportEvents::delete_many()
+            .filter(project_report_events::Column::ProjectReportId.eq(report_id as u32))
+            .exec(&ctx.db)
+            .await
+            .unwrap();
+
+        test::call_service(&app, resolve()).await;
+
+        // incident channels close what they opened, other channels aren't notified
+        let res: Value = test::call_and_read_body_json(&app, list_deliveries()).await;
+        assert_eq!(count_resolved(&res), 2);
+
+        // reports that are already resolved have no open incidents
+        test::call_service(&app, resolve()).await;
+
+        let res: Value = test::call_and_read_body_json(&app, list_deliveries()).await;
+        assert_eq!(count_resolved(&res), 2);
+
+        // merging a report away closes its incidents too
+        let req = test::TestRequest::post()
+            .uri("/ingress")
+            .set_json(serde_json::json!({
+                "key": api_key,
+                "data": {
+                    "title": "Connection pool timeout",
+                    "trace": "",
+                    "log": [],
+                    "os": "linux",
+                    "arch": "x86_64"
+                }
+            }))
+            .to_request();
+
+        test::call_service(&app, req).await;
+        crate::process_queues(&ctx).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/reports?project_id={}", project_id))
+            .cookie(sess.clone())
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        let source_id = res["reports"][0]["report"]["project_report_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/reports/{}/merge", report_id))
+            .cookie(sess.clone())
+            .set_json(vec![source_id])
+            .to_request();
+
+        let res: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(res["merged"], 1);
+
+        let res: Value = test::call_and_read_body_json(&app, list_deliveries()).await;
+        let merged = res["deliveries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|d| d["report_status"] == "merged")
+            .count();
+        assert_eq!(merged, 2);
+    }
+}
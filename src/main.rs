@@ -19,23 +19,30 @@ use chrono_tz::Tz;
 use handlebars::{Context, Handlebars, Helper, HelperResult, JsonRender, Output, RenderContext, RenderErrorReason};
 use key_lock::KeyLock;
 use lettre::{transport::smtp::PoolConfig, AsyncSmtpTransport, Tokio1Executor};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, Notify};
 
 use sea_orm::{prelude::*, ConnectOptions, Database, IntoActiveModel, TryIntoModel};
 
 use migration::{Migrator, MigratorTrait};
 
+mod backtrace;
 mod config;
 mod cron;
 mod entity;
 mod entity_extensions;
 mod error;
+mod filters;
+mod grouping;
 mod handlers;
 mod identity;
 mod notifications;
+mod queue;
+mod rate_limit;
+mod releases;
 
 use config::Config;
 use notifications::Notification;
+use rate_limit::RateLimiter;
 
 pub use error::Error;
 pub use identity::Identity;
@@ -61,6 +68,12 @@ pub struct AppContext<'reg> {
     // when an event arrives for a project, it will wait until the previous event is processed
     // and it's lock is released
     pub locked_projects: Arc<KeyLock<u32>>,
+    // wakes up the ingress queue workers when a new event is accepted
+    pub ingress_queue: Arc<Notify>,
+    // wakes up the notification delivery worker when deliveries are queued
+    pub notification_queue: Arc<Notify>,
+    // events per minute of each project and report
+    pub rate_limiter: Arc<RateLimiter>,
 }
 
 impl AppContext<'static> {
@@ -91,7 +104,8 @@ impl AppContext<'static> {
             None
         };
 
-        let (notifications, mut notifications_rx) = broadcast::channel(1000);
+        // live updates for connected clients, delivery goes through the notification outbox
+        let (notifications, _) = broadcast::channel(1000);
 
         let ctx = Self {
             config,
@@ -100,25 +114,13 @@ impl AppContext<'static> {
             mailer,
             notifications,
             locked_projects: Arc::new(KeyLock::new()),
+            ingress_queue: Arc::new(Notify::new()),
+            notification_queue: Arc::new(Notify::new()),
+            rate_limiter: Arc::new(RateLimiter::new()),
         };
 
-        // message handler
-        let handler_context = ctx.clone();
-
-        actix_web::rt::spawn(async move {
-            log::info!("Notifications handler task started");
-
-            loop {
-                let Ok(notification) = notifications_rx.recv().await else {
-                    log::info!("Notifications handler receiving channel closed");
-                    break;
-                };
-
-                if let Err(e) = notifications::send(&handler_context, &notification).await {
-                    log::error!("Error sending notification: {:?} error: {:?}", notification, e);
-                }
-            }
-        });
+        queue::spawn_workers(&ctx);
+        notifications::outbox::spawn_worker(&ctx);
 
         Ok(ctx)
     }
@@ -155,8 +157,12 @@ impl AppContext<'static> {
             mailer: None,
             notifications,
             locked_projects: Arc::new(KeyLock::new()),
+            ingress_queue: Arc::new(Notify::new()),
+            notification_queue: Arc::new(Notify::new()),
+            rate_limiter: Arc::new(RateLimiter::new()),
         };
 
+        // tests process the queues with `process_queues` instead of background workers
         Ok(ctx)
     }
 }
@@ -216,7 +222,11 @@ async fn main() -> anyhow::Result<()> {
                     .build(),
             )
             .app_data(web::Data::new(ctx.clone()))
-            .configure(handlers::ingress::routes)
+            .service(
+                web::scope("/ingress")
+                    .app_data(handlers::ingress::json_config(&ctx.config))
+                    .configure(handlers::ingress::routes),
+            )
             .service(web::scope("/api").configure(handlers::routes))
             .service(
                 Files::new("/", "./frontend/dist")
@@ -353,18 +363,35 @@ async fn create_default_user(db: &DatabaseConnection, config: &Config) -> Result
 pub async fn test_app_with_auth() -> Result<(
     impl actix_web::dev::Service<actix_http::Request, Response = ServiceResponse, Error = actix_web::Error>,
     actix_web::cookie::Cookie<'static>,
+)> {
+    let (app, sess, _) = test_app_with_context().await?;
+
+    Ok((app, sess))
+}
+
+/// Same as `test_app_with_auth`, also returns the context for processing the queues
+#[cfg(test)]
+pub async fn test_app_with_context() -> Result<(
+    impl actix_web::dev::Service<actix_http::Request, Response = ServiceResponse, Error = actix_web::Error>,
+    actix_web::cookie::Cookie<'static>,
+    AppContext<'static>,
 )> {
     let _ = env_logger::builder().is_test(true).try_init();
 
     let ctx = crate::AppContext::testing().await.unwrap();
 
     let signing_key = Key::generate();
+    let ingress_json_config = handlers::ingress::json_config(&ctx.config);
 
     let app = actix_web::test::init_service(
         App::new()
             .wrap(SessionMiddleware::builder(CookieSessionStore::default(), signing_key.clone()).build())
-            .app_data(web::Data::new(ctx))
-            .configure(handlers::ingress::routes)
+            .app_data(web::Data::new(ctx.clone()))
+            .service(
+                web::scope("/ingress")
+                    .app_data(ingress_json_config)
+                    .configure(handlers::ingress::routes),
+            )
             .service(web::scope("/api").configure(handlers::routes)),
     )
     .await;
@@ -383,5 +410,12 @@ pub async fn test_app_with_auth() -> Result<(
 
     let sess = actix_web::cookie::Cookie::parse(sess.to_string()).unwrap();
 
-    Ok((app, sess))
+    Ok((app, sess, ctx))
+}
+
+/// Processes all queued events and then the notification deliveries they queued
+#[cfg(test)]
+pub async fn process_queues(ctx: &AppContext<'static>) {
+    while queue::process_next(ctx, 0, 1).await.unwrap() {}
+    while notifications::outbox::deliver_next(ctx).await.unwrap() {}
 }
@@ -1,17 +1,31 @@
 use core::panic;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use lettre::AsyncTransport;
-use reqwest::header::CONTENT_TYPE;
 use sea_orm::prelude::*;
-use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use validator::ValidateUrl;
 
 use crate::entity::{
-    prelude::*, project_environments, project_report_events, project_reports, project_user_settings, projects, users,
+    project_environments, project_notification_channels, project_report_events, project_reports, projects, users,
 };
 use crate::AppContext;
 
+const HTTP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+mod discord;
+mod matrix;
+mod opsgenie;
+pub mod outbox;
+mod pagerduty;
+mod slack;
+mod slack_webhook;
+mod teams_webhook;
+mod telegram;
+mod webhook;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum ReportStatus {
     New,
@@ -51,219 +65,198 @@ impl Notification {
     }
 }
 
-pub async fn send(ctx: &AppContext<'_>, notification: &Notification) -> Result<()> {
-    // notifications are not send if the report is not new or regressed
-    if notification.status.is_none() {
-        return Ok(());
-    }
-
-    let users: Vec<(users::Model, Option<project_user_settings::Model>)> = Users::find()
-        .filter(project_user_settings::Column::ProjectId.eq(notification.project.project_id))
-        .find_also_related(ProjectUserSettings)
-        .all(&ctx.db)
-        .await?;
-
-    let report_url = format!(
-        "{}://{}/view-report/{}",
-        ctx.config.scheme, ctx.config.base_url, notification.report.project_report_id
-    );
-
-    for (user, maybe_settings) in users {
-        if let Some(settings) = maybe_settings {
-            if settings.notify_email > 0 {
-                if let Err(e) = send_email(ctx, notification, &user, &report_url).await {
-                    log::error!("Error sending notification email: {:?}", e);
-                }
-            }
+/// Every channel a project can be notified through
+pub static CHANNELS: &[&dyn NotificationChannel] = &[
+    &slack::Slack,
+    &slack_webhook::SlackWebhook,
+    &teams_webhook::TeamsWebhook,
+    &discord::Discord,
+    &telegram::Telegram,
+    &matrix::Matrix,
+    &pagerduty::PagerDuty,
+    &opsgenie::Opsgenie,
+    &webhook::Webhook,
+];
+
+/// Finds a channel by the name its configuration is stored under
+pub fn channel(name: &str) -> Option<&'static dyn NotificationChannel> {
+    CHANNELS.iter().copied().find(|channel| channel.name() == name)
+}
 
-            if settings.notify_pushover > 0 {
-                if let Err(e) = send_pushover(ctx, notification, &user, &report_url).await {
-                    log::error!("Error sending pushover notification: {:?}", e);
-                }
-            }
-        }
-    }
+/// An integration report notifications are delivered to. Channels are configured per project in
+/// `project_notification_channels`, environments can override parts of the project configuration.
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    /// Name the channel configuration is stored under
+    fn name(&self) -> &'static str;
 
-    if let Err(e) = send_slack(ctx, notification, &report_url).await {
-        log::error!("Error sending slack app message: {:?}", e);
-    }
+    /// Checks a configuration before it is saved
+    fn validate(&self, config: &Value) -> Result<()>;
 
-    if let Err(e) = send_slack_webhook(ctx, notification, &report_url).await {
-        log::error!("Error sending slack message via webhook: {:?}", e);
+    /// Configuration keys that are never shown once saved
+    fn secret_keys(&self) -> &'static [&'static str] {
+        &[]
     }
 
-    if let Err(e) = send_teams_webhook(ctx, notification, &report_url).await {
-        log::error!("Error posting to MS Teams webhook: {:?}", e);
+    /// Delivers a notification, returns the response status when the channel talks HTTP.
+    /// Retries of a delivery get the same `delivery_id`.
+    async fn send(
+        &self,
+        ctx: &AppContext<'_>,
+        config: &Value,
+        notification: &Notification,
+        report_url: &str,
+        delivery_id: u32,
+    ) -> Result<Option<u16>>;
+
+    /// Sends a message confirming the channel is set up correctly
+    async fn send_test(&self, ctx: &AppContext<'_>, config: &Value, project: &projects::Model) -> Result<()>;
+
+    /// Incident channels close what they opened for a report once it's resolved
+    fn resolves_incidents(&self) -> bool {
+        false
     }
 
-    if let Err(e) = send_webhook(ctx, notification, &report_url).await {
-        log::error!("Error sending report via webhook: {:?}", e);
+    /// Closes the incident opened for a resolved report, which is keyed by the report uid
+    async fn resolve(
+        &self,
+        _ctx: &AppContext<'_>,
+        _config: &Value,
+        _report: &project_reports::Model,
+    ) -> Result<Option<u16>> {
+        Ok(None)
     }
-
-    Ok(())
 }
 
-pub async fn send_slack(_ctx: &AppContext<'_>, notification: &Notification, report_url: &str) -> Result<()> {
-    let project = &notification.project;
+/// Deserializes a channel configuration
+pub(crate) fn parse_config<T: DeserializeOwned>(config: &Value) -> Result<T> {
+    serde_json::from_value(config.clone()).map_err(|e| anyhow!("Invalid configuration: {}", e))
+}
 
-    let Some((token, mut channel)) = project.slack_bot_token.as_deref().zip(project.slack_channel.clone()) else {
-        return Ok(());
-    };
+/// Configuration of the channels that post to a single URL
+#[derive(Deserialize)]
+pub(crate) struct WebhookConfig {
+    pub url: String,
+}
 
-    if let Some(env_channel) = notification.environment.as_ref().and_then(|e| e.slack_channel.clone()) {
-        if env_channel == "-1" {
-            // Notifications are disabled for this env
-            return Ok(());
+impl WebhookConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self.url.validate_url() {
+            return Err(anyhow!("Please enter a valid URL"));
         }
 
-        channel = env_channel;
+        Ok(())
     }
+}
 
-    let mut params = get_slack_blocks(notification, report_url);
-    params["channel"] = channel.into();
-
-    let client = reqwest::Client::new();
-    let response: serde_json::Value = client
-        .post("https://slack.com/api/chat.postMessage")
-        .bearer_auth(token)
-        .header(CONTENT_TYPE, "application/json; charset=utf-8")
-        .json(&params)
-        .send()
-        .await?
-        .json()
-        .await?;
+/// A receiving service answered with an error status
+#[derive(Debug)]
+pub struct ResponseError {
+    pub status: u16,
+    pub body: String,
+}
 
-    if !response.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
-        log::error!("Error sending slack message: {:?}", response);
-        return Ok(());
+impl std::fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Responded with {}: {}", self.status, self.body)
     }
-
-    Ok(())
 }
 
-pub async fn send_slack_webhook(_ctx: &AppContext<'_>, notification: &Notification, report_url: &str) -> Result<()> {
-    let project = &notification.project;
+impl std::error::Error for ResponseError {}
 
-    let Some(mut webhook) = project.slack_webhook.clone() else {
-        return Ok(());
-    };
+// a receiving service that hangs would otherwise hold up all deliveries
+pub(crate) fn http_client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder().timeout(HTTP_TIMEOUT).build()?)
+}
 
-    if let Some(env_webhook) = notification.environment.as_ref().and_then(|e| e.slack_webhook.clone()) {
-        if env_webhook == "-1" {
-            // Notifications are disabled for this env
-            return Ok(());
-        }
+/// The status of a response, statuses other than 2xx are a `ResponseError`
+pub(crate) async fn response_code(res: reqwest::Response) -> Result<u16> {
+    let status = res.status();
 
-        webhook = env_webhook;
+    if !status.is_success() {
+        let body = res.text().await?;
+        return Err(ResponseError {
+            status: status.as_u16(),
+            body: body.chars().take(1000).collect(),
+        }
+        .into());
     }
 
-    let mut params = get_slack_blocks(notification, report_url);
-    params["username"] = "Don't Panic".into();
-    params["icon_url"] = "https://dontpanic.rs/static/favicon.png".into();
-
-    let client = reqwest::Client::new();
-    client.post(webhook).json(&params).send().await?;
+    Ok(status.as_u16())
+}
 
-    // TODO: log error response
+/// Posts a JSON payload, returning the response status
+pub(crate) async fn post_json(url: &str, params: &Value) -> Result<u16> {
+    let res = http_client()?.post(url).json(params).send().await?;
 
-    Ok(())
+    response_code(res).await
 }
 
-fn get_slack_blocks(notification: &Notification, report_url: &str) -> serde_json::Value {
-    let mut title = match notification.status {
-        Some(ReportStatus::New) => format!(
-            ":boom: New report on {} received {}",
-            notification.project.name, notification.report.title
-        ),
-        Some(ReportStatus::Regressed) => format!(
-            "Resolved report on {} reappeared: {}",
-            notification.project.name, notification.report.title
-        ),
-        Some(ReportStatus::Spiking { percentage }) => format!(
-            ":warning: Received events for '{}' on {} have spiked by {}%",
-            notification.report.title, notification.project.name, percentage
-        ),
-        None => "Unknown report status".to_string(),
-    };
-
-    let mut markdown = match notification.status {
-        Some(ReportStatus::New) => format!(
-            ":boom: New report on *{}* received {}",
-            notification.project.name, notification.report.title
-        ),
-        Some(ReportStatus::Regressed) => format!(
-            "Resolved report on *{}* reappeared: {}",
-            notification.project.name, notification.report.title
-        ),
-        Some(ReportStatus::Spiking { percentage }) => format!(
-            ":warning: Received events for '{}' on *{}* have spiked by {}%",
-            notification.report.title, notification.project.name, percentage
-        ),
-        None => "Unknown report status".to_string(),
-    };
+/// Applies an environment override on top of the project configuration, keys are replaced one by one
+pub fn merge_config(config: &Value, overrides: &Value) -> Value {
+    let mut merged = config.clone();
 
-    if let Some(environment) = notification.environment.as_ref() {
-        title.push_str(&format!(" in {}", environment.name));
-        markdown.push_str(&format!(" in *{}*", environment.name));
+    if let (Some(merged), Some(overrides)) = (merged.as_object_mut(), overrides.as_object()) {
+        for (key, value) in overrides {
+            merged.insert(key.clone(), value.clone());
+        }
     }
 
-    json!({
-        "text": title,
-        "blocks": [
-            {
-                "type": "section",
-                "text": {
-                    "type": "mrkdwn",
-                    "text": markdown
-                }
-            },
-            {
-                "type": "actions",
-                "elements": [
-                    {
-                        "type": "button",
-                        "text": {
-                            "type": "plain_text",
-                            "text": "View in Don't Panic"
-                        },
-                        "url": report_url
-                    }
-                ]
-            }
-        ]
-    })
+    merged
 }
 
-pub async fn send_webhook(_ctx: &AppContext<'_>, notification: &Notification, report_url: &str) -> Result<()> {
-    let project = &notification.project;
-
-    let Some(mut webhook) = project.webhook.clone() else {
-        return Ok(());
-    };
-
-    if let Some(env_webhook) = notification.environment.as_ref().and_then(|e| e.webhook.clone()) {
-        if env_webhook == "-1" {
-            // Notifications are disabled for this env
-            return Ok(());
+/// Channels a notification of an environment is sent to, with their configuration for that environment
+fn effective_channels(
+    rows: &[project_notification_channels::Model],
+    environment_id: Option<u32>,
+) -> Vec<(&'static dyn NotificationChannel, Value)> {
+    let parse = |row: &project_notification_channels::Model| match serde_json::from_str::<Value>(&row.config) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            log::warn!(
+                "Invalid notification channel config {}: {}",
+                row.project_notification_channel_id,
+                e
+            );
+            None
         }
+    };
 
-        webhook = env_webhook;
-    }
+    rows.iter()
+        .filter(|row| row.project_environment_id.is_none() && row.is_enabled > 0)
+        .filter_map(|row| {
+            let Some(channel) = channel(&row.channel) else {
+                log::warn!("Unknown notification channel {}", row.channel);
+                return None;
+            };
+
+            let mut config = parse(row)?;
+
+            let environment_row = rows.iter().find(|env_row| {
+                env_row.channel == row.channel
+                    && env_row.project_environment_id.is_some()
+                    && env_row.project_environment_id == environment_id
+            });
+
+            if let Some(environment_row) = environment_row {
+                if environment_row.is_enabled == 0 {
+                    return None;
+                }
 
-    let params = json!({
-        "status": notification.status,
-        "title": notification.report.title,
-        "project": notification.project.name,
-        "environment": notification.environment.as_ref().map(|e| &e.name),
-        "backtrace": notification.event.backtrace,
-        "log": notification.event.log,
-        "url": report_url,
-    });
+                config = merge_config(&config, &parse(environment_row)?);
+            }
 
-    let client = reqwest::Client::new();
-    client.post(webhook).json(&params).send().await?;
+            Some((channel, config))
+        })
+        .collect()
+}
 
-    Ok(())
+/// Link to a report in the web interface
+pub fn report_url(ctx: &AppContext<'_>, project_report_id: u32) -> String {
+    format!(
+        "{}://{}/view-report/{}",
+        ctx.config.scheme, ctx.config.base_url, project_report_id
+    )
 }
 
 pub async fn send_email(
@@ -271,7 +264,7 @@ pub async fn send_email(
     notification: &Notification,
     user: &users::Model,
     report_url: &str,
-) -> Result<()> {
+) -> Result<Option<u16>> {
     let template = match notification.status {
         Some(ReportStatus::New) => "email/new_report",
         Some(ReportStatus::Regressed) => "email/regressed_report",
@@ -299,7 +292,7 @@ pub async fn send_email(
         mailer.send(email).await?;
     }
 
-    Ok(())
+    Ok(None)
 }
 
 pub async fn send_pushover(
@@ -307,19 +300,17 @@ pub async fn send_pushover(
     notification: &Notification,
     user: &users::Model,
     report_url: &str,
-) -> Result<()> {
+) -> Result<Option<u16>> {
     let Some((token, user_key)) = ctx
         .config
         .pushover_app_token
         .as_deref()
         .zip(user.pushover_user_key.as_deref())
     else {
-        return Ok(());
+        return Ok(None);
     };
 
-    let client = reqwest::Client::new();
-
-    let res = client
+    let res = http_client()?
         .post("https://api.pushover.net/1/messages.json")
         .form(&[
             ("token", token),
@@ -330,107 +321,58 @@ pub async fn send_pushover(
         .send()
         .await?;
 
-    if !res.status().is_success() {
-        let body = res.text().await?;
-        log::error!("Error sending pushover notification: {}", body);
-    }
-
-    Ok(())
+    response_code(res).await.map(Some)
 }
 
-pub async fn send_teams_webhook(_ctx: &AppContext<'_>, notification: &Notification, report_url: &str) -> Result<()> {
-    let project = &notification.project;
-
-    let Some(mut webhook) = project.teams_webhook.clone() else {
-        return Ok(());
-    };
-
-    if let Some(env_webhook) = notification.environment.as_ref().and_then(|e| e.teams_webhook.clone()) {
-        if env_webhook == "-1" {
-            // Notifications are disabled for this env
-            return Ok(());
-        }
-
-        webhook = env_webhook;
-    }
-
-    let title = notification.message();
-
-    let params = json!({
-        "type": "message",
-        "attachments": [
-            {
-                "contentType": "application/vnd.microsoft.card.adaptive",
-                "content": {
-                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
-                    "type": "AdaptiveCard",
-                    "version": "1.0",
-                    "body": [
-                        {
-                            "type": "ColumnSet",
-                            "columns": [
-                                {
-                                    "type": "Column",
-                                    "width": "auto",
-                                    "items": [
-                                        {
-                                            "type": "Image",
-                                            "url": "https://dontpanic.rs/static/favicon.png",
-                                            "size": "small",
-                                        }
-                                    ]
-                                },
-                                {
-                                    "type": "Column",
-                                    "width": "stretch",
-                                    "verticalContentAlignment": "center",
-                                    "items": [
-                                        {
-                                            "type": "TextBlock",
-                                            "text": title,
-                                            "size": "medium",
-                                            "weight": "bolder",
-                                            "style": "heading",
-                                        }
-                                    ]
-                                }
-                            ]
-                        },
-                        {
-                            "type": "TextBlock",
-                            "text": notification.report.title,
-                            "wrap": "true",
-                        },
-                        {
-                            "type": "FactSet",
-                            "facts": [
-                                {
-                                    "title": "Environment",
-                                    "value": notification.environment.as_ref().map(|e| e.name.as_str()).unwrap_or("Not Set"),
-                                }
-                            ]
-                        },
-                    ],
-                    "actions": [
-                        {
-                            "type": "Action.OpenUrl",
-                            "title": "View in Don't Panic",
-                            "url": report_url,
-                            "style": "positive"
-                        }
-                    ]
-                }
-            }
-        ]
-    });
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_effective_channels() {
+        let row = |id, environment_id, channel: &str, config: Value, is_enabled| project_notification_channels::Model {
+            project_notification_channel_id: id,
+            project_id: 1,
+            project_environment_id: environment_id,
+            channel: channel.to_string(),
+            config: config.to_string(),
+            is_enabled,
+            created: Utc::now().naive_utc(),
+        };
 
-    let client = reqwest::Client::new();
-    let res = client.post(webhook).json(&params).send().await?;
+        let rows = vec![
+            row(1, None, "webhook", json!({ "url": "https://example.com/all" }), 1),
+            row(
+                2,
+                Some(1),
+                "webhook",
+                json!({ "url": "https://example.com/production" }),
+                1,
+            ),
+            row(3, Some(2), "webhook", json!({}), 0),
+            row(
+                4,
+                None,
+                "slack_webhook",
+                json!({ "url": "https://example.com/slack" }),
+                0,
+            ),
+            row(5, None, "carrier_pigeon", json!({}), 1),
+        ];
+
+        let urls = |environment_id| {
+            effective_channels(&rows, environment_id)
+                .into_iter()
+                .map(|(channel, config)| format!("{} {}", channel.name(), config["url"].as_str().unwrap()))
+                .collect::<Vec<_>>()
+        };
 
-    if !res.status().is_success() {
-        let body = res.text().await?;
-        log::error!("Error posting to MS Teams webhook: {}", body);
+        assert_eq!(urls(None), ["webhook https://example.com/all"]);
+        assert_eq!(urls(Some(1)), ["webhook https://example.com/production"]);
+        assert!(urls(Some(2)).is_empty());
+        assert_eq!(urls(Some(3)), ["webhook https://example.com/all"]);
     }
-
-    Ok(())
 }
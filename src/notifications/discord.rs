@@ -0,0 +1,98 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{parse_config, post_json, Notification, NotificationChannel, ReportStatus, WebhookConfig};
+use crate::entity::projects;
+use crate::AppContext;
+
+// embed accent colors
+const COLOR_NEW: u32 = 0xe74c3c;
+const COLOR_REGRESSED: u32 = 0xe67e22;
+const COLOR_SPIKING: u32 = 0xf1c40f;
+
+/// Discord channel webhook, messages are sent as embeds
+pub struct Discord;
+
+#[async_trait]
+impl NotificationChannel for Discord {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    fn validate(&self, config: &Value) -> Result<()> {
+        parse_config::<WebhookConfig>(config)?.validate()
+    }
+
+    async fn send(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        notification: &Notification,
+        report_url: &str,
+        _delivery_id: u32,
+    ) -> Result<Option<u16>> {
+        let config: WebhookConfig = parse_config(config)?;
+
+        let color = match notification.status {
+            Some(ReportStatus::Regressed) => COLOR_REGRESSED,
+            Some(ReportStatus::Spiking { .. }) => COLOR_SPIKING,
+            _ => COLOR_NEW,
+        };
+
+        let environment = notification
+            .environment
+            .as_ref()
+            .map(|e| e.name.as_str())
+            .unwrap_or("Not Set");
+
+        let params = message(json!({
+            "title": truncate(&notification.message(), 256),
+            "description": truncate(&notification.report.title, 4096),
+            "url": report_url,
+            "color": color,
+            "fields": [
+                { "name": "Project", "value": notification.project.name, "inline": true },
+                { "name": "Environment", "value": environment, "inline": true },
+            ],
+        }));
+
+        post_json(&config.url, &params).await.map(Some)
+    }
+
+    async fn send_test(&self, _ctx: &AppContext<'_>, config: &Value, project: &projects::Model) -> Result<()> {
+        let config: WebhookConfig = parse_config(config)?;
+
+        let params = message(json!({
+            "title": "Discord is working!",
+            "description": format!("I'll post here when project {} panic!()s", project.name),
+            "url": "https://dontpanic.rs",
+            "color": COLOR_NEW,
+        }));
+
+        post_json(&config.url, &params).await?;
+
+        Ok(())
+    }
+}
+
+fn message(embed: Value) -> Value {
+    json!({
+        "username": "Don't Panic",
+        "avatar_url": "https://dontpanic.rs/static/favicon.png",
+        "embeds": [embed],
+        // report titles come from panic messages, they shouldn't ping anyone
+        "allowed_mentions": { "parse": [] },
+    })
+}
+
+// discord rejects embeds with fields longer than its limits
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
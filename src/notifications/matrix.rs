@@ -0,0 +1,129 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use handlebars::html_escape;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use validator::ValidateUrl;
+
+use super::{http_client, parse_config, response_code, Notification, NotificationChannel};
+use crate::entity::projects;
+use crate::AppContext;
+
+/// Matrix room, messages are sent by a user the access token belongs to
+pub struct Matrix;
+
+#[derive(Deserialize)]
+struct MatrixConfig {
+    /// Base URL of the client API, like https://matrix.org
+    homeserver: String,
+    access_token: String,
+    /// Internal id of the room, starting with "!"
+    room_id: String,
+}
+
+impl MatrixConfig {
+    /// The homeserver ignores a message sent again with the same transaction id
+    async fn send_message(&self, txn_id: &str, body: String, formatted_body: String) -> Result<u16> {
+        let url = format!(
+            "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+            self.homeserver.trim_end_matches('/'),
+            urlencoding::encode(&self.room_id),
+            txn_id,
+        );
+
+        let params = json!({
+            "msgtype": "m.text",
+            "body": body,
+            "format": "org.matrix.custom.html",
+            "formatted_body": formatted_body,
+        });
+
+        let res = http_client()?
+            .put(url)
+            .bearer_auth(&self.access_token)
+            .json(&params)
+            .send()
+            .await?;
+
+        response_code(res).await
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Matrix {
+    fn name(&self) -> &'static str {
+        "matrix"
+    }
+
+    fn validate(&self, config: &Value) -> Result<()> {
+        let config: MatrixConfig = parse_config(config)?;
+
+        if !config.homeserver.validate_url() {
+            return Err(anyhow!("Please enter a valid homeserver URL"));
+        }
+
+        if config.access_token.trim().is_empty() {
+            return Err(anyhow!("Matrix access token is required"));
+        }
+
+        if !config.room_id.starts_with('!') {
+            return Err(anyhow!(
+                "Please enter the internal room id, it starts with ! and can be found in the room settings"
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn secret_keys(&self) -> &'static [&'static str] {
+        &["access_token"]
+    }
+
+    async fn send(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        notification: &Notification,
+        report_url: &str,
+        delivery_id: u32,
+    ) -> Result<Option<u16>> {
+        let config: MatrixConfig = parse_config(config)?;
+
+        let message = notification.message();
+        let body = format!("{}\n{}", message, report_url);
+        let formatted_body = format!(
+            "<strong>{}</strong><br><a href=\"{}\">View in Don't Panic</a>",
+            html_escape(&message),
+            html_escape(report_url),
+        );
+
+        // a retry of a delivery the homeserver already accepted isn't posted twice
+        let txn_id = format!("dontpanic-{}", delivery_id);
+
+        config.send_message(&txn_id, body, formatted_body).await.map(Some)
+    }
+
+    async fn send_test(&self, _ctx: &AppContext<'_>, config: &Value, project: &projects::Model) -> Result<()> {
+        let config: MatrixConfig = parse_config(config)?;
+
+        let body = format!(
+            "Matrix is working! I'll post here when project {} panic!()s",
+            project.name
+        );
+        let formatted_body = format!(
+            "Matrix is working! I'll post here when project <strong>{}</strong> panic!()s",
+            html_escape(&project.name)
+        );
+
+        // test messages aren't retried, the transaction id only has to be unique
+        let txn_id = format!(
+            "dontpanic-test-{}-{}",
+            chrono::Utc::now().timestamp_millis(),
+            rand::random::<u32>()
+        );
+
+        config.send_message(&txn_id, body, formatted_body).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{http_client, parse_config, response_code, Notification, NotificationChannel, ReportStatus};
+use crate::entity::{project_reports, projects};
+use crate::AppContext;
+
+const PRIORITIES: &[&str] = &["P1", "P2", "P3", "P4", "P5"];
+
+/// Opsgenie alerts, opened per report with the report uid as the alias
+pub struct Opsgenie;
+
+#[derive(Deserialize)]
+struct OpsgenieConfig {
+    /// Key of an API integration in Opsgenie
+    api_key: String,
+    /// Accounts hosted in the EU use a separate API
+    #[serde(default)]
+    eu: bool,
+    priority: Option<String>,
+}
+
+impl OpsgenieConfig {
+    fn alerts_url(&self) -> &'static str {
+        if self.eu {
+            "https://api.eu.opsgenie.com/v2/alerts"
+        } else {
+            "https://api.opsgenie.com/v2/alerts"
+        }
+    }
+
+    async fn post(&self, url: &str, params: &Value) -> Result<u16> {
+        let res = http_client()?
+            .post(url)
+            .header(reqwest::header::AUTHORIZATION, format!("GenieKey {}", self.api_key))
+            .json(params)
+            .send()
+            .await?;
+
+        response_code(res).await
+    }
+
+    async fn create(&self, params: &Value) -> Result<u16> {
+        self.post(self.alerts_url(), params).await
+    }
+
+    async fn close(&self, alias: &str) -> Result<u16> {
+        let url = format!(
+            "{}/{}/close?identifierType=alias",
+            self.alerts_url(),
+            urlencoding::encode(alias)
+        );
+
+        self.post(
+            &url,
+            &json!({ "source": "Don't Panic", "note": "Resolved in Don't Panic" }),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Opsgenie {
+    fn name(&self) -> &'static str {
+        "opsgenie"
+    }
+
+    fn validate(&self, config: &Value) -> Result<()> {
+        let config: OpsgenieConfig = parse_config(config)?;
+
+        if config.api_key.trim().is_empty() {
+            return Err(anyhow!("Opsgenie API key is required"));
+        }
+
+        if config
+            .priority
+            .is_some_and(|priority| !PRIORITIES.contains(&priority.as_str()))
+        {
+            return Err(anyhow!("Priority must be one of: {}", PRIORITIES.join(", ")));
+        }
+
+        Ok(())
+    }
+
+    fn secret_keys(&self) -> &'static [&'static str] {
+        &["api_key"]
+    }
+
+    async fn send(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        notification: &Notification,
+        report_url: &str,
+        _delivery_id: u32,
+    ) -> Result<Option<u16>> {
+        let config: OpsgenieConfig = parse_config(config)?;
+
+        let mut tags = vec![notification.project.name.clone()];
+        tags.extend(notification.environment.as_ref().map(|e| e.name.clone()));
+
+        // details only take string values
+        let mut details = json!({ "url": report_url });
+
+        if let Some(environment) = notification.environment.as_ref() {
+            details["environment"] = environment.name.clone().into();
+        }
+
+        if let Some(version) = notification.event.version.as_ref() {
+            details["version"] = version.clone().into();
+        }
+
+        if let Some(ReportStatus::Spiking { percentage }) = notification.status {
+            details["spiked_by_percent"] = percentage.to_string().into();
+        }
+
+        let params = json!({
+            // opsgenie rejects messages longer than 130 characters
+            "message": notification.message().chars().take(130).collect::<String>(),
+            "alias": notification.report.uid,
+            "description": format!("{}\n\n{}", notification.report.title, report_url),
+            "source": "Don't Panic",
+            "entity": notification.project.name,
+            "tags": tags,
+            "details": details,
+            "priority": config.priority.as_deref().unwrap_or("P1"),
+        });
+
+        config.create(&params).await.map(Some)
+    }
+
+    async fn send_test(&self, _ctx: &AppContext<'_>, config: &Value, project: &projects::Model) -> Result<()> {
+        let config: OpsgenieConfig = parse_config(config)?;
+
+        // closed right away, so nobody stays paged for a test
+        let alias = format!("dontpanic-test-{}", project.project_id);
+
+        let params = json!({
+            "message": format!("Opsgenie is working! Alerts will open here when {} panic!()s", project.name)
+                .chars()
+                .take(130)
+                .collect::<String>(),
+            "alias": alias,
+            "source": "Don't Panic",
+            "priority": "P5",
+        });
+
+        config.create(&params).await?;
+        config.close(&alias).await?;
+
+        Ok(())
+    }
+
+    fn resolves_incidents(&self) -> bool {
+        true
+    }
+
+    async fn resolve(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        report: &project_reports::Model,
+    ) -> Result<Option<u16>> {
+        let config: OpsgenieConfig = parse_config(config)?;
+
+        config.close(&report.uid).await.map(Some)
+    }
+}
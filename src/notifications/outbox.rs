@@ -0,0 +1,477 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::prelude::*;
+use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, Condition, IntoActiveModel, QueryOrder};
+use serde_json::Value;
+
+use super::{
+    effective_channels, report_url, send_email, send_pushover, Notification, NotificationChannel, ReportStatus,
+    ResponseError,
+};
+use crate::entity::prelude::*;
+use crate::entity::{
+    project_notification_channels, project_notification_deliveries, project_report_merges, project_reports,
+    project_user_settings,
+};
+use crate::AppContext;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_DELIVERED: &str = "delivered";
+pub const STATUS_FAILED: &str = "failed";
+
+// stored instead of a report status, closes incidents opened for the report
+const REPORT_RESOLVED: &str = "resolved";
+// same for a report merged into another one, its incidents are closed with the uid it had before the merge
+const REPORT_MERGED: &str = "merged";
+
+// channels of a single user, sent to members who turned them on in their project settings
+const CHANNEL_EMAIL: &str = "email";
+const CHANNEL_PUSHOVER: &str = "pushover";
+
+// after this many failed attempts a delivery is marked as failed, it can still be resent manually
+const MAX_ATTEMPTS: u32 = 6;
+// how long a claimed delivery is hidden from other server instances sharing the database
+const LEASE: chrono::Duration = chrono::Duration::minutes(5);
+// the worker is woken up for new notifications, polling picks up retries
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+enum Outcome {
+    Delivered(Option<u16>),
+    Failed(anyhow::Error),
+    /// Retrying won't help, like when the channel was removed in the meantime
+    Undeliverable(&'static str),
+}
+
+async fn channel_rows(
+    db: &impl ConnectionTrait,
+    project_id: u32,
+    environment_id: Option<u32>,
+) -> Result<Vec<project_notification_channels::Model>> {
+    let rows = ProjectNotificationChannels::find()
+        .filter(project_notification_channels::Column::ProjectId.eq(project_id))
+        .filter(
+            Condition::any()
+                .add(project_notification_channels::Column::ProjectEnvironmentId.is_null())
+                .add(project_notification_channels::Column::ProjectEnvironmentId.eq(environment_id)),
+        )
+        .all(db)
+        .await?;
+
+    Ok(rows)
+}
+
+/// Queues a delivery of the notification for each channel and user it goes to. Meant to run in the
+/// transaction storing what is notified about, the worker is woken up with `notify_waiters` after the commit.
+pub async fn enqueue(db: &impl ConnectionTrait, notification: &Notification) -> Result<()> {
+    // only new, regressed and spiking reports are notified about
+    let Some(status) = notification.status else {
+        return Ok(());
+    };
+
+    let project_id = notification.project.project_id;
+    let environment_id = notification.environment.as_ref().map(|e| e.project_environment_id);
+
+    let user_settings = ProjectUserSettings::find()
+        .filter(project_user_settings::Column::ProjectId.eq(project_id))
+        .all(db)
+        .await?;
+
+    let mut deliveries: Vec<(&str, Option<u32>)> = vec![];
+
+    for settings in user_settings {
+        if settings.notify_email > 0 {
+            deliveries.push((CHANNEL_EMAIL, Some(settings.user_id)));
+        }
+
+        if settings.notify_pushover > 0 {
+            deliveries.push((CHANNEL_PUSHOVER, Some(settings.user_id)));
+        }
+    }
+
+    let rows = channel_rows(db, project_id, environment_id).await?;
+
+    for (channel, _) in effective_channels(&rows, environment_id) {
+        deliveries.push((channel.name(), None));
+    }
+
+    if deliveries.is_empty() {
+        return Ok(());
+    }
+
+    let report_status = serde_json::to_string(&status)?;
+    let now = Utc::now().naive_utc();
+
+    let rows = deliveries
+        .into_iter()
+        .map(|(channel, user_id)| project_notification_deliveries::ActiveModel {
+            project_id: ActiveValue::set(project_id),
+            project_report_id: ActiveValue::set(notification.report.project_report_id),
+            project_report_event_id: ActiveValue::set(Some(notification.event.project_report_event_id)),
+            project_environment_id: ActiveValue::set(environment_id),
+            user_id: ActiveValue::set(user_id),
+            channel: ActiveValue::set(channel.into()),
+            report_status: ActiveValue::set(report_status.clone()),
+            status: ActiveValue::set(STATUS_PENDING.into()),
+            attempts: ActiveValue::set(0),
+            available_at: ActiveValue::set(now),
+            ..Default::default()
+        });
+
+    ProjectNotificationDeliveries::insert_many(rows).exec(db).await?;
+
+    Ok(())
+}
+
+/// Queues resolving the incidents of reports, for channels that open them
+pub async fn enqueue_resolved(db: &impl ConnectionTrait, reports: &[project_reports::Model]) -> Result<()> {
+    let incidents = reports
+        .iter()
+        .map(|report| Incident {
+            project_id: report.project_id,
+            project_report_id: report.project_report_id,
+            project_report_merge_id: None,
+            environment_id: report.project_environment_id,
+        })
+        .collect();
+
+    enqueue_closed(db, incidents, REPORT_RESOLVED).await
+}
+
+/// Queues resolving the incidents of reports merged into `target`,
+/// the incident key is looked up from the merge when delivering
+pub async fn enqueue_merged(
+    db: &impl ConnectionTrait,
+    target: &project_reports::Model,
+    merges: &[project_report_merges::Model],
+) -> Result<()> {
+    let incidents = merges
+        .iter()
+        .map(|merge| Incident {
+            project_id: target.project_id,
+            project_report_id: target.project_report_id,
+            project_report_merge_id: Some(merge.project_report_merge_id),
+            environment_id: merge.project_environment_id,
+        })
+        .collect();
+
+    enqueue_closed(db, incidents, REPORT_MERGED).await
+}
+
+/// An incident opened for a report, closed by a delivery
+struct Incident {
+    project_id: u32,
+    project_report_id: u32,
+    project_report_merge_id: Option<u32>,
+    environment_id: Option<u32>,
+}
+
+/// Queues a delivery closing each incident, for the channels that open them
+async fn enqueue_closed(db: &impl ConnectionTrait, incidents: Vec<Incident>, report_status: &str) -> Result<()> {
+    let now = Utc::now().naive_utc();
+    let mut deliveries = vec![];
+
+    for incident in incidents {
+        let rows = channel_rows(db, incident.project_id, incident.environment_id).await?;
+
+        let channels = effective_channels(&rows, incident.environment_id)
+            .into_iter()
+            .filter(|(channel, _)| channel.resolves_incidents());
+
+        for (channel, _) in channels {
+            deliveries.push(project_notification_deliveries::ActiveModel {
+                project_id: ActiveValue::set(incident.project_id),
+                project_report_id: ActiveValue::set(incident.project_report_id),
+                project_report_merge_id: ActiveValue::set(incident.project_report_merge_id),
+                project_environment_id: ActiveValue::set(incident.environment_id),
+                channel: ActiveValue::set(channel.name().into()),
+                report_status: ActiveValue::set(report_status.into()),
+                status: ActiveValue::set(STATUS_PENDING.into()),
+                attempts: ActiveValue::set(0),
+                available_at: ActiveValue::set(now),
+                ..Default::default()
+            });
+        }
+    }
+
+    if deliveries.is_empty() {
+        return Ok(());
+    }
+
+    ProjectNotificationDeliveries::insert_many(deliveries).exec(db).await?;
+
+    Ok(())
+}
+
+/// Queues a failed delivery again, with a fresh set of attempts. Returns `None` when the delivery
+/// is no longer failed, like when it was resent in the meantime.
+pub async fn resend(
+    ctx: &AppContext<'_>,
+    delivery: project_notification_deliveries::Model,
+) -> Result<Option<project_notification_deliveries::Model>> {
+    // only reset failed rows, a pending one might be sent by the worker right now
+    let res = ProjectNotificationDeliveries::update_many()
+        .col_expr(
+            project_notification_deliveries::Column::Status,
+            Expr::value(STATUS_PENDING),
+        )
+        .col_expr(project_notification_deliveries::Column::Attempts, Expr::value(0))
+        .col_expr(
+            project_notification_deliveries::Column::AvailableAt,
+            Expr::value(Utc::now().naive_utc()),
+        )
+        .filter(
+            project_notification_deliveries::Column::ProjectNotificationDeliveryId
+                .eq(delivery.project_notification_delivery_id),
+        )
+        .filter(project_notification_deliveries::Column::Status.eq(STATUS_FAILED))
+        .exec(&ctx.db)
+        .await?;
+
+    if res.rows_affected == 0 {
+        return Ok(None);
+    }
+
+    ctx.notification_queue.notify_waiters();
+
+    let delivery = ProjectNotificationDeliveries::find_by_id(delivery.project_notification_delivery_id)
+        .one(&ctx.db)
+        .await?;
+
+    Ok(delivery)
+}
+
+pub fn spawn_worker(ctx: &AppContext<'static>) {
+    actix_web::rt::spawn(worker(ctx.clone()));
+}
+
+async fn worker(ctx: AppContext<'static>) {
+    log::info!("Notification delivery worker started");
+
+    loop {
+        // register for wake ups before checking the outbox, so notifications in between aren't missed
+        let notified = ctx.notification_queue.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        loop {
+            match deliver_next(&ctx).await {
+                Ok(true) => continue,
+                Ok(false) => break,
+                Err(e) => {
+                    log::error!("Error reading notification outbox: {:?}", e);
+                    break;
+                }
+            }
+        }
+
+        tokio::select! {
+            _ = notified => {},
+            _ = tokio::time::sleep(POLL_INTERVAL) => {},
+        }
+    }
+}
+
+/// Returns false when there is nothing left to deliver
+pub(crate) async fn deliver_next(ctx: &AppContext<'_>) -> Result<bool> {
+    let now = Utc::now().naive_utc();
+
+    let maybe_row = ProjectNotificationDeliveries::find()
+        .filter(project_notification_deliveries::Column::Status.eq(STATUS_PENDING))
+        .filter(project_notification_deliveries::Column::AvailableAt.lte(now))
+        .order_by_asc(project_notification_deliveries::Column::ProjectNotificationDeliveryId)
+        .one(&ctx.db)
+        .await?;
+
+    let Some(row) = maybe_row else {
+        return Ok(false);
+    };
+
+    // claim the delivery, if the row changed in the meantime another instance got it first
+    let claim = ProjectNotificationDeliveries::update_many()
+        .col_expr(
+            project_notification_deliveries::Column::AvailableAt,
+            Expr::value(now + LEASE),
+        )
+        .filter(
+            project_notification_deliveries::Column::ProjectNotificationDeliveryId
+                .eq(row.project_notification_delivery_id),
+        )
+        .filter(project_notification_deliveries::Column::Status.eq(STATUS_PENDING))
+        .filter(project_notification_deliveries::Column::AvailableAt.eq(row.available_at))
+        .exec(&ctx.db)
+        .await?;
+
+    if claim.rows_affected == 0 {
+        return Ok(true);
+    }
+
+    let outcome = deliver(ctx, &row).await.unwrap_or_else(Outcome::Failed);
+
+    let attempts = row.attempts + 1;
+    let delivery_id = row.project_notification_delivery_id;
+
+    let mut row = row.into_active_model();
+    row.attempts = ActiveValue::set(attempts);
+
+    match outcome {
+        Outcome::Delivered(response_code) => {
+            row.status = ActiveValue::set(STATUS_DELIVERED.into());
+            row.response_code = ActiveValue::set(response_code.map(u32::from));
+            row.error = ActiveValue::set(None);
+            row.delivered = ActiveValue::set(Some(Utc::now().naive_utc()));
+        }
+        Outcome::Failed(e) => {
+            log::warn!("Notification delivery {} failed: {:#}", delivery_id, e);
+
+            let response_code = e.downcast_ref::<ResponseError>().map(|e| u32::from(e.status));
+
+            row.response_code = ActiveValue::set(response_code);
+            row.error = ActiveValue::set(Some(format!("{:#}", e)));
+
+            if attempts >= MAX_ATTEMPTS {
+                row.status = ActiveValue::set(STATUS_FAILED.into());
+            } else {
+                // exponential backoff, 30s, 1m, 2m ... capped at one hour
+                let delay = chrono::Duration::seconds((30 * 2i64.pow(attempts - 1)).min(3600));
+                row.available_at = ActiveValue::set(Utc::now().naive_utc() + delay);
+            }
+        }
+        Outcome::Undeliverable(reason) => {
+            row.status = ActiveValue::set(STATUS_FAILED.into());
+            row.response_code = ActiveValue::set(None);
+            row.error = ActiveValue::set(Some(reason.into()));
+        }
+    }
+
+    row.update(&ctx.db).await?;
+
+    Ok(true)
+}
+
+/// Channel of a delivery with the configuration of its environment
+async fn delivery_channel(
+    ctx: &AppContext<'_>,
+    row: &project_notification_deliveries::Model,
+) -> Result<Option<(&'static dyn NotificationChannel, Value)>> {
+    let rows = channel_rows(&ctx.db, row.project_id, row.project_environment_id).await?;
+
+    let channel = effective_channels(&rows, row.project_environment_id)
+        .into_iter()
+        .find(|(channel, _)| channel.name() == row.channel);
+
+    Ok(channel)
+}
+
+async fn deliver(ctx: &AppContext<'_>, row: &project_notification_deliveries::Model) -> Result<Outcome> {
+    let Some(report) = ProjectReports::find_by_id(row.project_report_id).one(&ctx.db).await? else {
+        return Ok(Outcome::Undeliverable("The report no longer exists"));
+    };
+
+    if let REPORT_RESOLVED | REPORT_MERGED = row.report_status.as_str() {
+        return close_incident(ctx, row, report).await;
+    }
+
+    let Some(project) = Projects::find_by_id(row.project_id).one(&ctx.db).await? else {
+        return Ok(Outcome::Undeliverable("The project no longer exists"));
+    };
+
+    let event = match row.project_report_event_id {
+        Some(event_id) => ProjectReportEvents::find_by_id(event_id).one(&ctx.db).await?,
+        None => None,
+    };
+
+    let Some(event) = event else {
+        return Ok(Outcome::Undeliverable(
+            "The event of this notification no longer exists",
+        ));
+    };
+
+    let environment = match row.project_environment_id {
+        Some(environment_id) => ProjectEnvironments::find_by_id(environment_id).one(&ctx.db).await?,
+        None => None,
+    };
+
+    let status: ReportStatus = serde_json::from_str(&row.report_status)?;
+    let report_url = report_url(ctx, report.project_report_id);
+
+    let notification = Notification {
+        status: Some(status),
+        project,
+        event,
+        report,
+        environment,
+    };
+
+    let res = match row.user_id {
+        Some(user_id) => {
+            let Some(user) = Users::find_by_id(user_id).one(&ctx.db).await? else {
+                return Ok(Outcome::Undeliverable("The user no longer exists"));
+            };
+
+            match row.channel.as_str() {
+                CHANNEL_EMAIL => send_email(ctx, &notification, &user, &report_url).await,
+                CHANNEL_PUSHOVER => send_pushover(ctx, &notification, &user, &report_url).await,
+                _ => return Ok(Outcome::Undeliverable("Unknown channel")),
+            }
+        }
+        None => {
+            let Some((channel, config)) = delivery_channel(ctx, row).await? else {
+                return Ok(Outcome::Undeliverable("The channel is no longer configured"));
+            };
+
+            channel
+                .send(
+                    ctx,
+                    &config,
+                    &notification,
+                    &report_url,
+                    row.project_notification_delivery_id,
+                )
+                .await
+        }
+    };
+
+    Ok(match res {
+        Ok(response_code) => Outcome::Delivered(response_code),
+        Err(e) => Outcome::Failed(e),
+    })
+}
+
+/// Closes the incident opened for a resolved or merged report, only its uid is needed
+async fn close_incident(
+    ctx: &AppContext<'_>,
+    row: &project_notification_deliveries::Model,
+    mut report: project_reports::Model,
+) -> Result<Outcome> {
+    // a merged report is identified by the uid kept with the merge
+    if row.report_status == REPORT_MERGED {
+        let merge = match row.project_report_merge_id {
+            Some(merge_id) => ProjectReportMerges::find_by_id(merge_id).one(&ctx.db).await?,
+            None => None,
+        };
+
+        let Some(merge) = merge else {
+            return Ok(Outcome::Undeliverable("The report was unmerged in the meantime"));
+        };
+
+        report.uid = merge.uid;
+        report.title = merge.title;
+    }
+
+    if row.user_id.is_some() {
+        return Ok(Outcome::Undeliverable("Users aren't notified about resolved reports"));
+    }
+
+    let Some((channel, config)) = delivery_channel(ctx, row).await? else {
+        return Ok(Outcome::Undeliverable("The channel is no longer configured"));
+    };
+
+    let res = channel.resolve(ctx, &config, &report).await;
+
+    Ok(match res {
+        Ok(response_code) => Outcome::Delivered(response_code),
+        Err(e) => Outcome::Failed(e),
+    })
+}
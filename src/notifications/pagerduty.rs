@@ -0,0 +1,145 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{parse_config, post_json, Notification, NotificationChannel, ReportStatus};
+use crate::entity::{project_reports, projects};
+use crate::AppContext;
+
+const EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+const SEVERITIES: &[&str] = &["critical", "error", "warning", "info"];
+
+/// PagerDuty Events API v2, opens an incident per report, deduplicated by the report uid
+pub struct PagerDuty;
+
+#[derive(Deserialize)]
+struct PagerDutyConfig {
+    /// Integration key of an Events API v2 integration on a service
+    routing_key: String,
+    severity: Option<String>,
+}
+
+#[async_trait]
+impl NotificationChannel for PagerDuty {
+    fn name(&self) -> &'static str {
+        "pagerduty"
+    }
+
+    fn validate(&self, config: &Value) -> Result<()> {
+        let config: PagerDutyConfig = parse_config(config)?;
+
+        if config.routing_key.len() != 32 || !config.routing_key.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(anyhow!("Please enter the 32 character integration key of the service"));
+        }
+
+        if config
+            .severity
+            .is_some_and(|severity| !SEVERITIES.contains(&severity.as_str()))
+        {
+            return Err(anyhow!("Severity must be one of: {}", SEVERITIES.join(", ")));
+        }
+
+        Ok(())
+    }
+
+    fn secret_keys(&self) -> &'static [&'static str] {
+        &["routing_key"]
+    }
+
+    async fn send(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        notification: &Notification,
+        report_url: &str,
+        _delivery_id: u32,
+    ) -> Result<Option<u16>> {
+        let config: PagerDutyConfig = parse_config(config)?;
+
+        let environment = notification.environment.as_ref().map(|e| e.name.as_str());
+
+        let mut details = json!({
+            "title": notification.report.title,
+            "os": notification.event.os,
+            "arch": notification.event.arch,
+            "version": notification.event.version,
+        });
+
+        if let Some(ReportStatus::Spiking { percentage }) = notification.status {
+            details["spiked_by_percent"] = percentage.into();
+        }
+
+        let params = json!({
+            "routing_key": config.routing_key,
+            "event_action": "trigger",
+            "dedup_key": notification.report.uid,
+            "payload": {
+                // pagerduty cuts summaries at 1024 characters
+                "summary": notification.message().chars().take(1024).collect::<String>(),
+                "source": notification.project.name,
+                "severity": config.severity.as_deref().unwrap_or("critical"),
+                "component": notification.project.name,
+                "group": environment,
+                "custom_details": details,
+            },
+            "links": [{ "href": report_url, "text": "View in Don't Panic" }],
+            "client": "Don't Panic",
+            "client_url": report_url,
+        });
+
+        post_json(EVENTS_URL, &params).await.map(Some)
+    }
+
+    async fn send_test(&self, _ctx: &AppContext<'_>, config: &Value, project: &projects::Model) -> Result<()> {
+        let config: PagerDutyConfig = parse_config(config)?;
+
+        // resolved right away, so nobody stays paged for a test
+        let dedup_key = format!("dontpanic-test-{}", project.project_id);
+
+        let params = json!({
+            "routing_key": config.routing_key,
+            "event_action": "trigger",
+            "dedup_key": dedup_key,
+            "payload": {
+                "summary": format!("PagerDuty is working! Incidents will open here when project {} panic!()s", project.name),
+                "source": project.name,
+                "severity": "info",
+            },
+            "client": "Don't Panic",
+        });
+
+        post_json(EVENTS_URL, &params).await?;
+
+        let params = json!({
+            "routing_key": config.routing_key,
+            "event_action": "resolve",
+            "dedup_key": dedup_key,
+        });
+
+        post_json(EVENTS_URL, &params).await?;
+
+        Ok(())
+    }
+
+    fn resolves_incidents(&self) -> bool {
+        true
+    }
+
+    async fn resolve(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        report: &project_reports::Model,
+    ) -> Result<Option<u16>> {
+        let config: PagerDutyConfig = parse_config(config)?;
+
+        let params = json!({
+            "routing_key": config.routing_key,
+            "event_action": "resolve",
+            "dedup_key": report.uid,
+        });
+
+        post_json(EVENTS_URL, &params).await.map(Some)
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::header::CONTENT_TYPE;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{http_client, parse_config, Notification, NotificationChannel, ReportStatus};
+use crate::entity::projects;
+use crate::AppContext;
+
+/// Slack app installed through OAuth, posts to a channel the bot is a member of
+pub struct Slack;
+
+#[derive(Deserialize)]
+struct SlackConfig {
+    bot_token: Option<String>,
+    channel: Option<String>,
+}
+
+impl SlackConfig {
+    fn credentials(self) -> Option<(String, String)> {
+        self.bot_token.zip(self.channel)
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Slack {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    fn validate(&self, config: &Value) -> Result<()> {
+        let config: SlackConfig = parse_config(config)?;
+
+        if config.channel.is_some_and(|channel| channel.is_empty()) {
+            return Err(anyhow!("Slack channel is required"));
+        }
+
+        Ok(())
+    }
+
+    fn secret_keys(&self) -> &'static [&'static str] {
+        &["bot_token"]
+    }
+
+    async fn send(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        notification: &Notification,
+        report_url: &str,
+        _delivery_id: u32,
+    ) -> Result<Option<u16>> {
+        // the app is installed before a channel is picked
+        let Some((token, channel)) = parse_config::<SlackConfig>(config)?.credentials() else {
+            return Ok(None);
+        };
+
+        let mut params = get_slack_blocks(notification, report_url);
+        params["channel"] = channel.into();
+
+        post_message(&token, &params).await.map(Some)
+    }
+
+    async fn send_test(&self, _ctx: &AppContext<'_>, config: &Value, _project: &projects::Model) -> Result<()> {
+        let Some((token, channel)) = parse_config::<SlackConfig>(config)?.credentials() else {
+            return Err(anyhow!("Slack App not configured"));
+        };
+
+        let params = json!({
+            "channel": channel,
+            "text": "You have successfully configured Slack App for notifications!",
+        });
+
+        post_message(&token, &params).await?;
+
+        Ok(())
+    }
+}
+
+// the slack api answers errors with a 200 and `ok: false`
+async fn post_message(token: &str, params: &Value) -> Result<u16> {
+    let res = http_client()?
+        .post("https://slack.com/api/chat.postMessage")
+        .bearer_auth(token)
+        .header(CONTENT_TYPE, "application/json; charset=utf-8")
+        .json(params)
+        .send()
+        .await?;
+
+    let status = res.status().as_u16();
+    let response: Value = res.json().await?;
+
+    if !response.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Err(anyhow!("Error sending slack message: {:?}", response));
+    }
+
+    Ok(status)
+}
+
+pub(super) fn get_slack_blocks(notification: &Notification, report_url: &str) -> serde_json::Value {
+    let mut title = match notification.status {
+        Some(ReportStatus::New) => format!(
+            ":boom: New report on {} received {}",
+            notification.project.name, notification.report.title
+        ),
+        Some(ReportStatus::Regressed) => format!(
+            "Resolved report on {} reappeared: {}",
+            notification.project.name, notification.report.title
+        ),
+        Some(ReportStatus::Spiking { percentage }) => format!(
+            ":warning: Received events for '{}' on {} have spiked by {}%",
+            notification.report.title, notification.project.name, percentage
+        ),
+        None => "Unknown report status".to_string(),
+    };
+
+    let mut markdown = match notification.status {
+        Some(ReportStatus::New) => format!(
+            ":boom: New report on *{}* received {}",
+            notification.project.name, notification.report.title
+        ),
+        Some(ReportStatus::Regressed) => format!(
+            "Resolved report on *{}* reappeared: {}",
+            notification.project.name, notification.report.title
+        ),
+        Some(ReportStatus::Spiking { percentage }) => format!(
+            ":warning: Received events for '{}' on *{}* have spiked by {}%",
+            notification.report.title, notification.project.name, percentage
+        ),
+        None => "Unknown report status".to_string(),
+    };
+
+    if let Some(environment) = notification.environment.as_ref() {
+        title.push_str(&format!(" in {}", environment.name));
+        markdown.push_str(&format!(" in *{}*", environment.name));
+    }
+
+    json!({
+        "text": title,
+        "blocks": [
+            {
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": markdown
+                }
+            },
+            {
+                "type": "actions",
+                "elements": [
+                    {
+                        "type": "button",
+                        "text": {
+                            "type": "plain_text",
+                            "text": "View in Don't Panic"
+                        },
+                        "url": report_url
+                    }
+                ]
+            }
+        ]
+    })
+}
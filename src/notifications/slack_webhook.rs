@@ -0,0 +1,54 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::slack::get_slack_blocks;
+use super::{parse_config, post_json, Notification, NotificationChannel, WebhookConfig};
+use crate::entity::projects;
+use crate::AppContext;
+
+/// Slack incoming webhook
+pub struct SlackWebhook;
+
+#[async_trait]
+impl NotificationChannel for SlackWebhook {
+    fn name(&self) -> &'static str {
+        "slack_webhook"
+    }
+
+    fn validate(&self, config: &Value) -> Result<()> {
+        parse_config::<WebhookConfig>(config)?.validate()
+    }
+
+    async fn send(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        notification: &Notification,
+        report_url: &str,
+        _delivery_id: u32,
+    ) -> Result<Option<u16>> {
+        let config: WebhookConfig = parse_config(config)?;
+
+        let mut params = get_slack_blocks(notification, report_url);
+        params["username"] = "Don't Panic".into();
+        params["icon_url"] = "https://dontpanic.rs/static/favicon.png".into();
+
+        post_json(&config.url, &params).await.map(Some)
+    }
+
+    async fn send_test(&self, _ctx: &AppContext<'_>, config: &Value, project: &projects::Model) -> Result<()> {
+        let config: WebhookConfig = parse_config(config)?;
+
+        let params = json!({
+            "text": format!(
+                "Slack is working! I'll post here when project {} panic!()s",
+                project.name
+            ),
+        });
+
+        post_json(&config.url, &params).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{parse_config, post_json, Notification, NotificationChannel, WebhookConfig};
+use crate::entity::projects;
+use crate::AppContext;
+
+/// Microsoft Teams workflow webhook, messages are sent as adaptive cards
+pub struct TeamsWebhook;
+
+#[async_trait]
+impl NotificationChannel for TeamsWebhook {
+    fn name(&self) -> &'static str {
+        "teams_webhook"
+    }
+
+    fn validate(&self, config: &Value) -> Result<()> {
+        parse_config::<WebhookConfig>(config)?.validate()
+    }
+
+    async fn send(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        notification: &Notification,
+        report_url: &str,
+        _delivery_id: u32,
+    ) -> Result<Option<u16>> {
+        let config: WebhookConfig = parse_config(config)?;
+
+        let environment = notification
+            .environment
+            .as_ref()
+            .map(|e| e.name.as_str())
+            .unwrap_or("Not Set");
+
+        let card = adaptive_card(
+            &notification.message(),
+            vec![
+                json!({
+                    "type": "TextBlock",
+                    "text": notification.report.title,
+                    "wrap": "true",
+                }),
+                json!({
+                    "type": "FactSet",
+                    "facts": [
+                        {
+                            "title": "Environment",
+                            "value": environment,
+                        }
+                    ]
+                }),
+            ],
+            vec![json!({
+                "type": "Action.OpenUrl",
+                "title": "View in Don't Panic",
+                "url": report_url,
+                "style": "positive"
+            })],
+        );
+
+        post_json(&config.url, &card).await.map(Some)
+    }
+
+    async fn send_test(&self, _ctx: &AppContext<'_>, config: &Value, project: &projects::Model) -> Result<()> {
+        let config: WebhookConfig = parse_config(config)?;
+
+        let card = adaptive_card(
+            "Don't Panic MS Teams integration is working",
+            vec![json!({
+                "type": "TextBlock",
+                "text": format!(
+                    "You'll get notified in this channel when '{}' experiences errors or panics.",
+                    project.name
+                ),
+                "wrap": "true",
+            })],
+            vec![],
+        );
+
+        post_json(&config.url, &card).await?;
+
+        Ok(())
+    }
+}
+
+fn adaptive_card(title: &str, body: Vec<Value>, actions: Vec<Value>) -> Value {
+    let header = json!({
+        "type": "ColumnSet",
+        "columns": [
+            {
+                "type": "Column",
+                "width": "auto",
+                "items": [
+                    {
+                        "type": "Image",
+                        "url": "https://dontpanic.rs/static/favicon.png",
+                        "size": "small",
+                    }
+                ]
+            },
+            {
+                "type": "Column",
+                "width": "stretch",
+                "verticalContentAlignment": "center",
+                "items": [
+                    {
+                        "type": "TextBlock",
+                        "text": title,
+                        "size": "medium",
+                        "weight": "bolder",
+                        "style": "heading",
+                    }
+                ]
+            }
+        ]
+    });
+
+    json!({
+        "type": "message",
+        "attachments": [
+            {
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.0",
+                    "body": std::iter::once(header).chain(body).collect::<Vec<_>>(),
+                    "actions": actions,
+                }
+            }
+        ]
+    })
+}
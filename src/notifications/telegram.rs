@@ -0,0 +1,108 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use handlebars::html_escape;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{http_client, parse_config, response_code, Notification, NotificationChannel};
+use crate::entity::projects;
+use crate::AppContext;
+
+/// Telegram bot, posts to a chat, group or channel the bot was added to
+pub struct Telegram;
+
+#[derive(Deserialize)]
+struct TelegramConfig {
+    bot_token: String,
+    /// Numeric id of the chat or @username of a public channel
+    chat_id: String,
+}
+
+impl TelegramConfig {
+    async fn send_message(&self, text: String) -> Result<u16> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+
+        let params = json!({
+            "chat_id": self.chat_id,
+            "text": text,
+            "parse_mode": "HTML",
+            "link_preview_options": { "is_disabled": true },
+        });
+
+        // the token is part of the URL, it must not end up in the delivery log
+        let res = http_client()?
+            .post(url)
+            .json(&params)
+            .send()
+            .await
+            .map_err(|e| e.without_url())?;
+
+        response_code(res).await
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Telegram {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    fn validate(&self, config: &Value) -> Result<()> {
+        let config: TelegramConfig = parse_config(config)?;
+
+        let valid_token = config.bot_token.split_once(':').is_some_and(|(bot_id, secret)| {
+            !bot_id.is_empty()
+                && bot_id.chars().all(|c| c.is_ascii_digit())
+                && !secret.is_empty()
+                && secret
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+
+        if !valid_token {
+            return Err(anyhow!("Please enter the bot token given by @BotFather"));
+        }
+
+        if config.chat_id.trim().is_empty() {
+            return Err(anyhow!("Telegram chat id is required"));
+        }
+
+        Ok(())
+    }
+
+    fn secret_keys(&self) -> &'static [&'static str] {
+        &["bot_token"]
+    }
+
+    async fn send(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        notification: &Notification,
+        report_url: &str,
+        _delivery_id: u32,
+    ) -> Result<Option<u16>> {
+        let config: TelegramConfig = parse_config(config)?;
+
+        let text = format!(
+            "<b>{}</b>\n\n<a href=\"{}\">View in Don't Panic</a>",
+            html_escape(&notification.message()),
+            html_escape(report_url),
+        );
+
+        config.send_message(text).await.map(Some)
+    }
+
+    async fn send_test(&self, _ctx: &AppContext<'_>, config: &Value, project: &projects::Model) -> Result<()> {
+        let config: TelegramConfig = parse_config(config)?;
+
+        let text = format!(
+            "Telegram is working! I'll post here when project <b>{}</b> panic!()s",
+            html_escape(&project.name)
+        );
+
+        config.send_message(text).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use super::{http_client, parse_config, response_code, Notification, NotificationChannel, ReportStatus, WebhookConfig};
+use crate::entity::{project_report_events, projects};
+use crate::AppContext;
+
+const SIGNATURE_HEADER: &str = "x-dontpanic-signature";
+const TIMESTAMP_HEADER: &str = "x-dontpanic-timestamp";
+
+// set by the server, custom headers can't replace them
+const RESERVED_HEADERS: &[&str] = &[
+    "content-type",
+    "content-length",
+    "host",
+    SIGNATURE_HEADER,
+    TIMESTAMP_HEADER,
+];
+
+#[derive(Deserialize)]
+struct Config {
+    #[serde(flatten)]
+    webhook: WebhookConfig,
+    /// Signs each request with HMAC-SHA256 when set
+    secret: Option<String>,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    /// Includes the whole event, with its metadata, tags and context
+    #[serde(default)]
+    full_payload: bool,
+}
+
+/// Posts the report as JSON to any URL
+pub struct Webhook;
+
+#[async_trait]
+impl NotificationChannel for Webhook {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn validate(&self, config: &Value) -> Result<()> {
+        let config: Config = parse_config(config)?;
+        config.webhook.validate()?;
+
+        if config.secret.as_deref().is_some_and(|secret| secret.trim().is_empty()) {
+            return Err(anyhow!("The signing secret can't be empty"));
+        }
+
+        for (name, value) in &config.headers {
+            let Ok(name) = HeaderName::from_bytes(name.as_bytes()) else {
+                return Err(anyhow!("Invalid header name: {}", name));
+            };
+
+            if RESERVED_HEADERS.contains(&name.as_str()) {
+                return Err(anyhow!("The {} header can't be changed", name));
+            }
+
+            if HeaderValue::from_str(value).is_err() {
+                return Err(anyhow!("Invalid value of the {} header", name));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn secret_keys(&self) -> &'static [&'static str] {
+        // headers usually carry credentials, like an authorization token
+        &["secret", "headers"]
+    }
+
+    async fn send(
+        &self,
+        _ctx: &AppContext<'_>,
+        config: &Value,
+        notification: &Notification,
+        report_url: &str,
+        _delivery_id: u32,
+    ) -> Result<Option<u16>> {
+        let config: Config = parse_config(config)?;
+
+        let mut params = json!({
+            "status": notification.status,
+            "title": notification.report.title,
+            "project": notification.project.name,
+            "environment": notification.environment.as_ref().map(|e| &e.name),
+            "backtrace": notification.event.backtrace,
+            "log": notification.event.log,
+            "url": report_url,
+        });
+
+        if config.full_payload {
+            params["report"] = json!({
+                "project_report_id": notification.report.project_report_id,
+                "first_seen": notification.report.created,
+                "last_seen": notification.report.last_seen,
+            });
+            params["event"] = full_event(&notification.event);
+        }
+
+        post(&config, &params).await.map(Some)
+    }
+
+    async fn send_test(&self, _ctx: &AppContext<'_>, config: &Value, project: &projects::Model) -> Result<()> {
+        let config: Config = parse_config(config)?;
+
+        let example_log = json!([{
+            "timestamp": chrono::Utc::now().timestamp() as u64,
+            "level": 1,
+            "message": "Error message",
+            "module": "my_module",
+            "file": "src/main.rs",
+            "line": 42,
+        }]);
+
+        let mut params = json!({
+            "status": ReportStatus::New,
+            "title": "Called `Option::unwrap()` on a `None` value (Webhook Test)",
+            "project": project.name,
+            "environment": "development",
+            "backtrace": EXAMPLE_BACKTRACE,
+            "log": serde_json::to_string(&example_log)?,
+            "url": "https://dontpanic.rs",
+        });
+
+        if config.full_payload {
+            params["report"] = json!({
+                "project_report_id": 0,
+                "first_seen": chrono::Utc::now().naive_utc(),
+                "last_seen": chrono::Utc::now().naive_utc(),
+            });
+            params["event"] = json!({
+                "project_report_event_id": 0,
+                "backtrace": EXAMPLE_BACKTRACE,
+                "log": example_log,
+                "os": "linux",
+                "arch": "x86_64",
+                "version": "1.0.0",
+                "thread_id": "ThreadId(1)",
+                "thread_name": "main",
+                "location": { "file": "src/main.rs", "line": 4, "column": 5 },
+                "tags": { "region": "eu" },
+                "context": null,
+                "received": chrono::Utc::now().naive_utc(),
+            });
+        }
+
+        post(&config, &params).await?;
+
+        Ok(())
+    }
+}
+
+/// The event as it was reported, with the JSON columns parsed
+fn full_event(event: &project_report_events::Model) -> Value {
+    let parse = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(|value| serde_json::from_str::<Value>(value).ok())
+    };
+
+    json!({
+        "project_report_event_id": event.project_report_event_id,
+        "backtrace": event.backtrace,
+        "log": parse(&event.log),
+        "os": event.os,
+        "arch": event.arch,
+        "version": event.version,
+        "thread_id": event.thread_id,
+        "thread_name": event.thread_name,
+        "location": {
+            "file": event.location_file,
+            "line": event.location_line,
+            "column": event.location_column,
+        },
+        "tags": parse(&event.tags),
+        "context": parse(&event.context),
+        "received": event.received,
+    })
+}
+
+async fn post(config: &Config, params: &Value) -> Result<u16> {
+    let body = serde_json::to_vec(params)?;
+
+    let mut req = http_client()?
+        .post(&config.webhook.url)
+        .header(CONTENT_TYPE, "application/json");
+
+    for (name, value) in &config.headers {
+        req = req.header(name, value);
+    }
+
+    if let Some(secret) = config.secret.as_deref() {
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+
+        req = req.header(TIMESTAMP_HEADER, &timestamp).header(
+            SIGNATURE_HEADER,
+            format!("sha256={}", signature(secret, &timestamp, &body)),
+        );
+    }
+
+    let res = req.body(body).send().await?;
+
+    response_code(res).await
+}
+
+/// HMAC-SHA256 of the timestamp and the body joined with a dot, as lowercase hex.
+/// Receivers should also reject old timestamps, so a captured request can't be replayed.
+fn signature(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+const EXAMPLE_BACKTRACE: &str = r#"stack backtrace:
+0: playground::main::h6849180917e9510b (0x55baf1676201)
+            at src/main.rs:4
+1: std::rt::lang_start::{{closure}}::hb3ceb20351fe39ee (0x55baf1675faf)
+            at /rustc/3c235d5600393dfe6c36eeed34042efad8d4f26e/src/libstd/rt.rs:64
+2: {{closure}} (0x55baf16be492)
+            at src/libstd/rt.rs:49
+    do_call<closure,i32>
+            at src/libstd/panicking.rs:293
+3: __rust_maybe_catch_panic (0x55baf16c00b9)
+            at src/libpanic_unwind/lib.rs:87
+4: try<i32,closure> (0x55baf16bef9c)
+            at src/libstd/panicking.rs:272
+    catch_unwind<closure,i32>
+            at src/libstd/panic.rs:388
+    lang_start_internal
+            at src/libstd/rt.rs:48
+5: std::rt::lang_start::h2c4217f9057b6ddb (0x55baf1675f88)
+            at /rustc/3c235d5600393dfe6c36eeed34042efad8d4f26e/src/libstd/rt.rs:64
+6: main (0x55baf16762f9)
+7: __libc_start_main (0x7fab051b9b96)
+8: _start (0x55baf1675e59)
+9: <unknown> (0x0)"#;
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        assert_eq!(
+            signature("whsec_test", "1760000000", br#"{"title":"Test"}"#),
+            "34eb3961cd2c8e24d2896c2e227dee3148ecb0a49140bac76f221437fb7c0740"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let webhook = Webhook;
+
+        assert!(webhook
+            .validate(&json!({
+                "url": "https://example.com/hook",
+                "secret": "whsec_test",
+                "headers": { "Authorization": "Bearer token" },
+                "full_payload": true,
+            }))
+            .is_ok());
+
+        assert!(webhook
+            .validate(&json!({ "url": "https://example.com/hook", "secret": " " }))
+            .is_err());
+        assert!(webhook
+            .validate(&json!({ "url": "https://example.com/hook", "headers": { "bad header": "1" } }))
+            .is_err());
+        assert!(webhook
+            .validate(&json!({ "url": "https://example.com/hook", "headers": { "X-Dontpanic-Signature": "1" } }))
+            .is_err());
+    }
+}
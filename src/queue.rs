@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::prelude::*;
+use sea_orm::prelude::*;
+use sea_orm::{ActiveValue, IntoActiveModel, QueryOrder};
+
+use crate::entity::prelude::*;
+use crate::entity::queued_events;
+use crate::handlers::ingress;
+use crate::{AppContext, Error};
+
+const STATUS_PENDING: &str = "pending";
+const STATUS_DEAD: &str = "dead";
+
+// after this many failed attempts an event is moved to the dead-letter state
+const MAX_ATTEMPTS: u32 = 10;
+// dead-lettered events are kept this long for inspection and requeueing
+const DEAD_RETENTION: chrono::Duration = chrono::Duration::days(14);
+// how long a claimed event is hidden from other workers (and other server instances sharing the database)
+const LEASE: chrono::Duration = chrono::Duration::minutes(5);
+// workers are woken up on every push, polling only picks up retries and events from other instances
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Persists an accepted event. Once this returns the event will be processed, even across restarts.
+pub async fn push(
+    ctx: &AppContext<'_>,
+    project_id: u32,
+    payload: String,
+    client_ip_hash: Option<String>,
+    project_key_id: u32,
+) -> Result<()> {
+    let row = queued_events::ActiveModel {
+        project_id: ActiveValue::set(project_id),
+        payload: ActiveValue::set(payload),
+        client_ip_hash: ActiveValue::set(client_ip_hash),
+        project_key_id: ActiveValue::set(Some(project_key_id)),
+        status: ActiveValue::set(STATUS_PENDING.into()),
+        attempts: ActiveValue::set(0),
+        available_at: ActiveValue::set(Utc::now().naive_utc()),
+        ..Default::default()
+    };
+
+    row.insert(&ctx.db).await?;
+
+    ctx.ingress_queue.notify_waiters();
+
+    Ok(())
+}
+
+/// Moves all dead-lettered events back to the queue
+pub async fn requeue_dead(ctx: &AppContext<'_>) -> Result<u64> {
+    let res = QueuedEvents::update_many()
+        .col_expr(queued_events::Column::Status, Expr::value(STATUS_PENDING))
+        .col_expr(queued_events::Column::Attempts, Expr::value(0))
+        .col_expr(queued_events::Column::AvailableAt, Expr::value(Utc::now().naive_utc()))
+        .filter(queued_events::Column::Status.eq(STATUS_DEAD))
+        .exec(&ctx.db)
+        .await?;
+
+    ctx.ingress_queue.notify_waiters();
+
+    Ok(res.rows_affected)
+}
+
+/// Deletes events that have been dead-lettered for longer than the retention period
+pub async fn prune_dead(ctx: &AppContext<'_>) -> Result<u64> {
+    let res = QueuedEvents::delete_many()
+        .filter(queued_events::Column::Status.eq(STATUS_DEAD))
+        .filter(queued_events::Column::AvailableAt.lt(Utc::now().naive_utc() - DEAD_RETENTION))
+        .exec(&ctx.db)
+        .await?;
+
+    Ok(res.rows_affected)
+}
+
+pub fn spawn_workers(ctx: &AppContext<'static>) {
+    let workers = ctx.config.ingress_workers;
+
+    for worker_idx in 0..workers {
+        actix_web::rt::spawn(worker(ctx.clone(), worker_idx, workers));
+    }
+}
+
+// Each worker owns the projects where project_id % workers == worker_idx,
+// this way events of a single project are always processed in the order they were received
+async fn worker(ctx: AppContext<'static>, worker_idx: u32, workers: u32) {
+    log::info!("Ingress queue worker {} started", worker_idx);
+
+    loop {
+        // register for wake ups before checking the queue, so pushes in between aren't missed
+        let notified = ctx.ingress_queue.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        loop {
+            match process_next(&ctx, worker_idx, workers).await {
+                Ok(true) => continue,
+                Ok(false) => break,
+                Err(e) => {
+                    log::error!("Error reading ingress queue: {:?}", e);
+                    break;
+                }
+            }
+        }
+
+        tokio::select! {
+            _ = notified => {},
+            _ = tokio::time::sleep(POLL_INTERVAL) => {},
+        }
+    }
+}
+
+/// Returns false when there is nothing left to process
+pub(crate) async fn process_next(ctx: &AppContext<'static>, worker_idx: u32, workers: u32) -> Result<bool> {
+    let now = Utc::now().naive_utc();
+
+    let maybe_row = QueuedEvents::find()
+        .filter(queued_events::Column::Status.eq(STATUS_PENDING))
+        .filter(queued_events::Column::AvailableAt.lte(now))
+        .filter(Expr::cust_with_values("project_id % ? = ?", [workers, worker_idx]))
+        .order_by_asc(queued_events::Column::QueuedEventId)
+        .one(&ctx.db)
+        .await?;
+
+    let Some(row) = maybe_row else {
+        return Ok(false);
+    };
+
+    // claim the event, if the row changed in the meantime another instance got it first
+    let claim = QueuedEvents::update_many()
+        .col_expr(queued_events::Column::AvailableAt, Expr::value(now + LEASE))
+        .col_expr(
+            queued_events::Column::Attempts,
+            Expr::col(queued_events::Column::Attempts).add(1),
+        )
+        .filter(queued_events::Column::QueuedEventId.eq(row.queued_event_id))
+        .filter(queued_events::Column::AvailableAt.eq(row.available_at))
+        .exec(&ctx.db)
+        .await?;
+
+    if claim.rows_affected == 0 {
+        return Ok(true);
+    }
+
+    let res = {
+        let _lock = ctx.locked_projects.lock(row.project_id).await;
+        ingress::process_queued(ctx, &row).await
+    };
+
+    let attempts = row.attempts + 1;
+
+    let (message, permanent) = match res {
+        // the event was removed from the queue together with storing it
+        Ok(()) => return Ok(true),
+        Err(Error::Internal(e)) => {
+            log::error!("Error processing event {}: {:?}", row.queued_event_id, e);
+            (format!("{:#}", e), false)
+        }
+        // user errors like a deleted project won't go away on retry
+        Err(e) => {
+            log::warn!("Event {} rejected: {}", row.queued_event_id, e);
+            (e.to_string(), true)
+        }
+    };
+
+    let mut row = row.into_active_model();
+    row.attempts = ActiveValue::set(attempts);
+    row.last_error = ActiveValue::set(Some(message));
+
+    if permanent || attempts >= MAX_ATTEMPTS {
+        // dead events keep the time they were dead-lettered, for pruning
+        row.status = ActiveValue::set(STATUS_DEAD.into());
+        row.available_at = ActiveValue::set(Utc::now().naive_utc());
+    } else {
+        // exponential backoff, 2s, 4s, 8s ... capped at one hour
+        let delay = chrono::Duration::seconds(2i64.pow(attempts).min(3600));
+        row.available_at = ActiveValue::set(Utc::now().naive_utc() + delay);
+    }
+
+    row.update(&ctx.db).await?;
+
+    Ok(true)
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::prelude::*;
+
+// weight of the last minute in a project's baseline, roughly an hourly moving average
+const BASELINE_WEIGHT: f64 = 1.0 / 60.0;
+// minutes a project has to be observed for before its baseline is trusted
+const BASELINE_WARMUP: i64 = 60;
+// spike protection never drops events below this rate per minute
+const MIN_SPIKE_EVENTS: f64 = 60.0;
+// report windows are pruned once this many are tracked
+const MAX_REPORT_WINDOWS: usize = 10_000;
+
+/// Why an event was not accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limited {
+    Project,
+    Report,
+    Spike,
+}
+
+impl Limited {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Project => "project",
+            Self::Report => "report",
+            Self::Spike => "spike",
+        }
+    }
+}
+
+/// Events per minute allowed for a project and each of its reports
+#[derive(Debug, Default)]
+pub struct Limits {
+    pub project: Option<u32>,
+    pub report: Option<u32>,
+    /// Multiple of the project's baseline rate at which spike protection kicks in
+    pub spike_multiplier: Option<u32>,
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    minute: i64,
+    count: u32,
+}
+
+#[derive(Debug)]
+struct ProjectWindow {
+    window: Window,
+    // events received this minute, including the ones over the limits
+    attempts: u32,
+    first_minute: i64,
+    baseline: f64,
+}
+
+impl ProjectWindow {
+    fn new(minute: i64) -> Self {
+        Self {
+            window: Window { minute, count: 0 },
+            attempts: 0,
+            first_minute: minute,
+            baseline: 0.0,
+        }
+    }
+
+    // folds the finished minutes into the baseline, minutes without events count as zero
+    fn roll(&mut self, minute: i64) {
+        let elapsed = minute - self.window.minute;
+
+        if elapsed <= 0 {
+            return;
+        }
+
+        self.baseline = self.baseline * (1.0 - BASELINE_WEIGHT) + self.window.count as f64 * BASELINE_WEIGHT;
+        self.baseline *= (1.0 - BASELINE_WEIGHT).powi((elapsed - 1).min(1000) as i32);
+        self.window = Window { minute, count: 0 };
+        self.attempts = 0;
+    }
+
+    fn spike_threshold(&self, multiplier: u32) -> Option<f64> {
+        if self.window.minute - self.first_minute < BASELINE_WARMUP {
+            return None;
+        }
+
+        Some((self.baseline * multiplier as f64).max(MIN_SPIKE_EVENTS))
+    }
+
+    fn is_spiking(&self, multiplier: u32) -> bool {
+        self.spike_threshold(multiplier)
+            .is_some_and(|threshold| self.window.count as f64 >= threshold)
+    }
+}
+
+/// Counts accepted events per minute. Counters are kept in memory, so limits apply per server process.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    projects: Mutex<HashMap<u32, ProjectWindow>>,
+    reports: Mutex<HashMap<String, Window>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts the event if it's within the limits
+    pub fn check(&self, project_id: u32, report_uid: &str, limits: &Limits) -> Result<(), Limited> {
+        self.check_at(Utc::now().timestamp() / 60, project_id, report_uid, limits)
+    }
+
+    fn check_at(&self, minute: i64, project_id: u32, report_uid: &str, limits: &Limits) -> Result<(), Limited> {
+        let mut projects = self.projects.lock().unwrap();
+        let project = projects.entry(project_id).or_insert_with(|| ProjectWindow::new(minute));
+        project.roll(minute);
+        project.attempts += 1;
+
+        if limits.project.is_some_and(|limit| project.window.count >= limit) {
+            return Err(Limited::Project);
+        }
+
+        if limits
+            .spike_multiplier
+            .is_some_and(|multiplier| project.is_spiking(multiplier))
+        {
+            return Err(Limited::Spike);
+        }
+
+        if let Some(limit) = limits.report {
+            let mut reports = self.reports.lock().unwrap();
+
+            if reports.len() >= MAX_REPORT_WINDOWS {
+                reports.retain(|_, window| window.minute == minute);
+            }
+
+            let report = reports.entry(report_uid.to_string()).or_default();
+
+            if report.minute != minute {
+                *report = Window { minute, count: 0 };
+            }
+
+            if report.count >= limit {
+                return Err(Limited::Report);
+            }
+
+            report.count += 1;
+        }
+
+        project.window.count += 1;
+
+        Ok(())
+    }
+
+    /// Share of this minute's events that fit in the project limits, clients are asked to sample at this rate
+    pub fn sample_rate(&self, project_id: u32, limits: &Limits) -> f64 {
+        self.sample_rate_at(Utc::now().timestamp() / 60, project_id, limits)
+    }
+
+    fn sample_rate_at(&self, minute: i64, project_id: u32, limits: &Limits) -> f64 {
+        let projects = self.projects.lock().unwrap();
+
+        let Some(project) = projects.get(&project_id).filter(|p| p.window.minute == minute) else {
+            return 1.0;
+        };
+
+        let allowed = [
+            limits.project.map(f64::from),
+            limits
+                .spike_multiplier
+                .and_then(|multiplier| project.spike_threshold(multiplier)),
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(f64::min);
+
+        match allowed {
+            Some(allowed) if project.attempts as f64 > allowed => {
+                // two decimals are plenty for a client side sampler
+                ((allowed / project.attempts as f64 * 100.0).floor() / 100.0).max(0.01)
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let limiter = RateLimiter::new();
+
+        let limits = Limits {
+            project: Some(3),
+            report: Some(2),
+            spike_multiplier: None,
+        };
+
+        assert_eq!(limiter.check_at(0, 1, "a", &limits), Ok(()));
+        assert_eq!(limiter.check_at(0, 1, "a", &limits), Ok(()));
+        assert_eq!(limiter.check_at(0, 1, "a", &limits), Err(Limited::Report));
+        assert_eq!(limiter.check_at(0, 1, "b", &limits), Ok(()));
+        assert_eq!(limiter.check_at(0, 1, "c", &limits), Err(Limited::Project));
+
+        assert_eq!(limiter.check_at(0, 1, "d", &limits), Err(Limited::Project));
+        assert_eq!(limiter.sample_rate_at(0, 1, &limits), 0.5);
+
+        // counters start over every minute
+        assert_eq!(limiter.check_at(1, 1, "a", &limits), Ok(()));
+        assert_eq!(limiter.sample_rate_at(1, 1, &limits), 1.0);
+    }
+
+    #[test]
+    fn test_spike_protection() {
+        let limiter = RateLimiter::new();
+
+        let limits = Limits {
+            spike_multiplier: Some(10),
+            ..Default::default()
+        };
+
+        // a steady 20 events per minute
+        for minute in 0..120 {
+            for _ in 0..20 {
+                assert_eq!(limiter.check_at(minute, 1, "a", &limits), Ok(()));
+            }
+        }
+
+        let accepted = (0..1000)
+            .filter(|_| limiter.check_at(120, 1, "a", &limits).is_ok())
+            .count();
+
+        // the baseline is a bit under 20 after two hours
+        assert!((150..200).contains(&accepted), "accepted {}", accepted);
+        assert_eq!(limiter.check_at(121, 1, "a", &limits), Ok(()));
+    }
+}
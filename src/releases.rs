@@ -0,0 +1,94 @@
+use std::cmp::Ordering;
+
+use anyhow::Result;
+use chrono::prelude::*;
+use sea_orm::{prelude::*, sea_query, ActiveValue};
+
+use crate::entity::prelude::*;
+use crate::entity::project_releases;
+
+/// Records a version sent with an event, the release is created the first time it is seen
+pub async fn record(db: &impl ConnectionTrait, project_id: u32, version: &str) -> Result<()> {
+    let now = Utc::now().naive_utc();
+
+    let release = project_releases::ActiveModel {
+        project_id: ActiveValue::set(project_id),
+        version: ActiveValue::set(version.into()),
+        first_seen: ActiveValue::set(now),
+        last_seen: ActiveValue::set(now),
+        ..Default::default()
+    };
+
+    ProjectReleases::insert(release)
+        .on_conflict(
+            sea_query::OnConflict::columns([project_releases::Column::ProjectId, project_releases::Column::Version])
+                .value(project_releases::Column::LastSeen, now)
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// The newest release of a project
+pub async fn latest(db: &impl ConnectionTrait, project_id: u32) -> Result<Option<String>> {
+    let releases = ProjectReleases::find()
+        .filter(project_releases::Column::ProjectId.eq(project_id))
+        .all(db)
+        .await?;
+
+    Ok(releases.into_iter().max_by(cmp_releases).map(|r| r.version))
+}
+
+/// Whether `version` is a newer release than `than`
+pub async fn is_newer(db: &impl ConnectionTrait, project_id: u32, version: &str, than: &str) -> Result<bool> {
+    if let (Some(a), Some(b)) = (parse(version), parse(than)) {
+        return Ok(a > b);
+    }
+
+    let releases = ProjectReleases::find()
+        .filter(project_releases::Column::ProjectId.eq(project_id))
+        .filter(project_releases::Column::Version.is_in([version, than]))
+        .all(db)
+        .await?;
+
+    let find = |v: &str| releases.iter().find(|r| r.version == v);
+
+    let newer = match (find(version), find(than)) {
+        (Some(a), Some(b)) => cmp_releases(a, b) == Ordering::Greater,
+        // a version we have never seen before can only be a new one
+        (None, Some(_)) => true,
+        _ => false,
+    };
+
+    Ok(newer)
+}
+
+// Semantic versions are compared as such, anything else is ordered by when it was first seen
+fn cmp_releases(a: &project_releases::Model, b: &project_releases::Model) -> Ordering {
+    match (parse(&a.version), parse(&b.version)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => a.project_release_id.cmp(&b.project_release_id),
+    }
+}
+
+/// Parses a semantic version, a leading `v` is allowed
+pub fn parse(version: &str) -> Option<semver::Version> {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+
+    semver::Version::parse(version).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert!(parse("v1.2.3").unwrap() > parse("1.2.3-beta.1").unwrap());
+        assert!(parse("1.10.0").unwrap() > parse("1.9.9").unwrap());
+        assert!(parse("2024-10-01").is_none());
+    }
+}